@@ -0,0 +1,73 @@
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use crypto_pals::encoding::base64::{self, Config};
+
+/// The original `chars().nth()` encoder, kept as the comparison baseline.
+fn legacy_encode(input: &[u8]) -> String {
+    const BASE64_TABLE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+
+    let mut i = 0;
+    while i < input.len() {
+        let chunk = &input[i..(i + 3).min(input.len())];
+        i += 3;
+
+        let mut buffer = [0u8; 3];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+
+        let b0 = buffer[0] >> 2;
+        let b1 = ((buffer[0] & 0b00000011) << 4) | (buffer[1] >> 4);
+        let b2 = ((buffer[1] & 0b00001111) << 2) | (buffer[2] >> 6);
+        let b3 = buffer[2] & 0b00111111;
+
+        output.push(BASE64_TABLE.chars().nth(b0 as usize).unwrap());
+        output.push(BASE64_TABLE.chars().nth(b1 as usize).unwrap());
+
+        if chunk.len() > 1 {
+            output.push(BASE64_TABLE.chars().nth(b2 as usize).unwrap());
+        } else {
+            output.push('=');
+        }
+
+        if chunk.len() == 3 {
+            output.push(BASE64_TABLE.chars().nth(b3 as usize).unwrap());
+        } else {
+            output.push('=');
+        }
+    }
+
+    output
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("base64_encode");
+    group.sample_size(10);
+
+    for mib in [1, 4] {
+        let input: Vec<u8> = (0..mib << 20).map(|i| (i * 131 + 17) as u8).collect();
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("legacy", mib), &input, |b, input| {
+            b.iter(|| legacy_encode(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("encode", mib), &input, |b, input| {
+            b.iter(|| base64::encode(black_box(input)))
+        });
+
+        let mut output = vec![0u8; base64::encoded_len(input.len(), Config::STANDARD)];
+        group.bench_with_input(BenchmarkId::new("encode_slice", mib), &input, |b, input| {
+            b.iter(|| base64::encode_slice(black_box(input), &mut output, Config::STANDARD))
+        });
+
+        let mut output = vec![0u8; base64::encoded_len(input.len(), Config::MIME)];
+        group.bench_with_input(
+            BenchmarkId::new("encode_slice_mime", mib),
+            &input,
+            |b, input| b.iter(|| base64::encode_slice(black_box(input), &mut output, Config::MIME)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode);
+criterion_main!(benches);
@@ -0,0 +1,286 @@
+pub const BLOCK_LEN: usize = 16;
+
+pub type Block = [u8; BLOCK_LEN];
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const INV_SBOX: [u8; 256] = {
+    let mut inv = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        inv[SBOX[i] as usize] = i as u8;
+        i += 1;
+    }
+    inv
+};
+
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// AES-128 (FIPS-197) on a byte-oriented state, one table lookup per S-box
+/// and no other tables, so each step can be run and inspected on its own.
+/// Not constant time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aes128 {
+    round_keys: [Block; Aes128::ROUNDS + 1],
+}
+
+impl Aes128 {
+    pub const ROUNDS: usize = 10;
+
+    pub fn new(key: &[u8; 16]) -> Self {
+        Aes128 {
+            round_keys: expand_key(key),
+        }
+    }
+
+    /// The initial whitening key followed by one key per round.
+    pub fn round_keys(&self) -> &[Block; Aes128::ROUNDS + 1] {
+        &self.round_keys
+    }
+
+    pub fn encrypt_block(&self, block: &mut Block) {
+        self.encrypt_rounds(block, Aes128::ROUNDS);
+    }
+
+    pub fn decrypt_block(&self, block: &mut Block) {
+        self.decrypt_rounds(block, Aes128::ROUNDS);
+    }
+
+    /// Encrypts with only the first `rounds` rounds; the last one run skips
+    /// MixColumns, as the full cipher's final round does.
+    ///
+    /// # Panics
+    ///
+    /// If `rounds` is not between 1 and [`Aes128::ROUNDS`].
+    pub fn encrypt_rounds(&self, block: &mut Block, rounds: usize) {
+        assert!((1..=Aes128::ROUNDS).contains(&rounds), "1 to 10 rounds");
+        add_round_key(block, &self.round_keys[0]);
+        for round in 1..=rounds {
+            sub_bytes(block);
+            shift_rows(block);
+            if round != rounds {
+                mix_columns(block);
+            }
+            add_round_key(block, &self.round_keys[round]);
+        }
+    }
+
+    /// Inverts [`Aes128::encrypt_rounds`] with the same `rounds`.
+    ///
+    /// # Panics
+    ///
+    /// If `rounds` is not between 1 and [`Aes128::ROUNDS`].
+    pub fn decrypt_rounds(&self, block: &mut Block, rounds: usize) {
+        assert!((1..=Aes128::ROUNDS).contains(&rounds), "1 to 10 rounds");
+        for round in (1..=rounds).rev() {
+            add_round_key(block, &self.round_keys[round]);
+            if round != rounds {
+                inv_mix_columns(block);
+            }
+            inv_shift_rows(block);
+            inv_sub_bytes(block);
+        }
+        add_round_key(block, &self.round_keys[0]);
+    }
+}
+
+pub fn expand_key(key: &[u8; 16]) -> [Block; Aes128::ROUNDS + 1] {
+    let mut round_keys = [[0u8; BLOCK_LEN]; Aes128::ROUNDS + 1];
+    round_keys[0] = *key;
+    for round in 1..=Aes128::ROUNDS {
+        let prev = round_keys[round - 1];
+        let mut word = [prev[13], prev[14], prev[15], prev[12]].map(|b| SBOX[b as usize]);
+        word[0] ^= RCON[round - 1];
+
+        let next = &mut round_keys[round];
+        for i in 0..BLOCK_LEN {
+            word[i % 4] ^= prev[i];
+            next[i] = word[i % 4];
+        }
+    }
+    round_keys
+}
+
+// The state is the block in FIPS-197 order: byte `4 * c + r` is row `r` of
+// column `c`.
+
+pub fn add_round_key(state: &mut Block, round_key: &Block) {
+    for (s, k) in state.iter_mut().zip(round_key) {
+        *s ^= k;
+    }
+}
+
+pub fn sub_bytes(state: &mut Block) {
+    for s in state.iter_mut() {
+        *s = SBOX[*s as usize];
+    }
+}
+
+pub fn inv_sub_bytes(state: &mut Block) {
+    for s in state.iter_mut() {
+        *s = INV_SBOX[*s as usize];
+    }
+}
+
+/// Rotates row `r` left by `r` columns.
+pub fn shift_rows(state: &mut Block) {
+    let old = *state;
+    for c in 0..4 {
+        for r in 0..4 {
+            state[4 * c + r] = old[4 * ((c + r) % 4) + r];
+        }
+    }
+}
+
+pub fn inv_shift_rows(state: &mut Block) {
+    let old = *state;
+    for c in 0..4 {
+        for r in 0..4 {
+            state[4 * ((c + r) % 4) + r] = old[4 * c + r];
+        }
+    }
+}
+
+pub fn mix_columns(state: &mut Block) {
+    for col in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [col[0], col[1], col[2], col[3]];
+        col[0] = gmul(a0, 2) ^ gmul(a1, 3) ^ a2 ^ a3;
+        col[1] = a0 ^ gmul(a1, 2) ^ gmul(a2, 3) ^ a3;
+        col[2] = a0 ^ a1 ^ gmul(a2, 2) ^ gmul(a3, 3);
+        col[3] = gmul(a0, 3) ^ a1 ^ a2 ^ gmul(a3, 2);
+    }
+}
+
+pub fn inv_mix_columns(state: &mut Block) {
+    for col in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [col[0], col[1], col[2], col[3]];
+        col[0] = gmul(a0, 14) ^ gmul(a1, 11) ^ gmul(a2, 13) ^ gmul(a3, 9);
+        col[1] = gmul(a0, 9) ^ gmul(a1, 14) ^ gmul(a2, 11) ^ gmul(a3, 13);
+        col[2] = gmul(a0, 13) ^ gmul(a1, 9) ^ gmul(a2, 14) ^ gmul(a3, 11);
+        col[3] = gmul(a0, 11) ^ gmul(a1, 13) ^ gmul(a2, 9) ^ gmul(a3, 14);
+    }
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1.
+fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_lc_rs::cipher::{AES_128, EncryptingKey, UnboundCipherKey};
+
+    use crate::aes::*;
+    use crate::encoding::hex_to_bytes;
+
+    fn block(hex: &str) -> Block {
+        hex_to_bytes(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_key_expansion() {
+        // FIPS-197 appendix A.1.
+        let aes = Aes128::new(&block("2b7e151628aed2a6abf7158809cf4f3c"));
+        let keys = aes.round_keys();
+        assert_eq!(keys[1], block("a0fafe1788542cb123a339392a6c7605"));
+        assert_eq!(keys[5], block("d4d1c6f87c839d87caf2b8bc11f915bc"));
+        assert_eq!(keys[10], block("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+    }
+
+    #[test]
+    fn test_fips_197_vectors() {
+        // Appendix B, then appendix C.1.
+        for (key, plaintext, ciphertext) in [
+            (
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "3243f6a8885a308d313198a2e0370734",
+                "3925841d02dc09fbdc118597196a0b32",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                "00112233445566778899aabbccddeeff",
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+        ] {
+            let aes = Aes128::new(&block(key));
+            let mut buf = block(plaintext);
+            aes.encrypt_block(&mut buf);
+            assert_eq!(buf, block(ciphertext));
+            aes.decrypt_block(&mut buf);
+            assert_eq!(buf, block(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_round_steps_invert() {
+        let mut state = block("00112233445566778899aabbccddeeff");
+        let original = state;
+        shift_rows(&mut state);
+        mix_columns(&mut state);
+        sub_bytes(&mut state);
+        assert_ne!(state, original);
+        inv_sub_bytes(&mut state);
+        inv_mix_columns(&mut state);
+        inv_shift_rows(&mut state);
+        assert_eq!(state, original);
+
+        let aes = Aes128::new(b"YELLOW SUBMARINE");
+        for rounds in 1..=Aes128::ROUNDS {
+            let mut buf = original;
+            aes.encrypt_rounds(&mut buf, rounds);
+            aes.decrypt_rounds(&mut buf, rounds);
+            assert_eq!(buf, original, "{rounds} rounds");
+        }
+    }
+
+    #[test]
+    fn test_matches_aws_lc() {
+        let key = *b"YELLOW SUBMARINE";
+        let plaintext: Vec<u8> = (0..64 * BLOCK_LEN).map(|i| (i * 7 + 1) as u8).collect();
+
+        let mut expected = plaintext.clone();
+        EncryptingKey::ecb(UnboundCipherKey::new(&AES_128, &key).unwrap())
+            .unwrap()
+            .encrypt(&mut expected)
+            .unwrap();
+
+        let aes = Aes128::new(&key);
+        for (chunk, expected) in plaintext
+            .chunks_exact(BLOCK_LEN)
+            .zip(expected.chunks_exact(BLOCK_LEN))
+        {
+            let mut buf: Block = chunk.try_into().unwrap();
+            aes.encrypt_block(&mut buf);
+            assert_eq!(buf, expected);
+        }
+    }
+}
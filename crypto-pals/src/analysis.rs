@@ -0,0 +1,137 @@
+pub mod ecb;
+pub mod key_size;
+pub mod language_model;
+pub mod repeating_key;
+pub mod scoring;
+pub mod single_byte;
+
+use std::{cmp::Eq, collections::HashMap, hash::Hash};
+
+use crate::xor::{XorError, check_lengths, words};
+
+pub use ecb::{EcbHit, EcbReport, detect_ecb, rank_ecb};
+pub use key_size::{KeySize, KeySizeConfig, KeySizeMethod, estimate_key_sizes, select_key_size};
+pub use language_model::LanguageModel;
+pub use repeating_key::{BreakError, BreakOptions, Recovered, break_repeating_key_xor};
+pub use scoring::Scorer;
+pub use single_byte::{Candidate, Hit, crack_single_byte_xor, detect_single_byte_xor};
+
+pub fn frequency_count<T>(items: &[T]) -> HashMap<&T, usize>
+where
+    T: Eq + Hash,
+{
+    let mut freq = HashMap::new();
+    for item in items {
+        *freq.entry(item).or_insert(0) += 1;
+    }
+    freq
+}
+
+pub fn most_frequent_item<T>(freq: &HashMap<T, usize>) -> Option<(&T, usize)>
+where
+    T: Eq + Hash,
+{
+    freq.iter()
+        .max_by_key(|&(_, &count)| count)
+        .map(|(item, &count)| (item, count))
+}
+
+pub fn character_frequency(b1: &str) -> HashMap<char, usize> {
+    let mut freq = HashMap::new();
+    for c in b1.chars() {
+        *freq.entry(c).or_insert(0) += 1;
+    }
+    freq
+}
+
+/// Number of differing bits, a `u64` at a time.
+pub fn hamming_distance(b1: &[u8], b2: &[u8]) -> Result<usize, XorError> {
+    check_lengths(b1, b2)?;
+    Ok(words(b1)
+        .zip(words(b2))
+        .map(|(w1, w2)| (w1 ^ w2).count_ones() as usize)
+        .sum())
+}
+
+/// The old name for [`hamming_distance`].
+pub fn edit_distance(b1: &[u8], b2: &[u8]) -> Result<usize, XorError> {
+    hamming_distance(b1, b2)
+}
+
+/// Two points per "etaoin shrdlu" letter, one per other letter, minus half
+/// per whitespace or punctuation byte. Non-ASCII bytes score nothing.
+pub fn score_plaintext(bytes: &[u8]) -> f32 {
+    let mut score = 0.0;
+    for b in bytes.iter().map(u8::to_ascii_lowercase) {
+        if b"etaoin shrdlu".contains(&b) {
+            score += 2.0;
+        } else if b.is_ascii_alphabetic() {
+            score += 1.0;
+        } else if b.is_ascii_whitespace() || b.is_ascii_punctuation() {
+            score -= 0.5;
+        }
+    }
+    score
+}
+
+/// The single most likely key size under the default Hamming estimate, or 0
+/// when the ciphertext is too short to measure.
+pub fn find_best_key_size(raw: &[u8]) -> usize {
+    estimate_key_sizes(raw, KeySizeConfig::DEFAULT)
+        .first()
+        .map_or(0, |size| size.key_size)
+}
+
+pub fn crack_columns(
+    transposed_blocks: Vec<Vec<u8>>,
+    scorer: &dyn Scorer,
+) -> (Vec<char>, Vec<Vec<u8>>) {
+    let mut keys = Vec::new();
+    let mut decrypted_columns = Vec::new();
+
+    for block in transposed_blocks {
+        let best = crack_single_byte_xor(&block, scorer, 1)
+            .pop()
+            .expect("256 candidates");
+        keys.push(best.key as char);
+        decrypted_columns.push(best.plaintext);
+    }
+
+    (keys, decrypted_columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::*;
+
+    #[test]
+    fn test_hamming() {
+        let s1 = "this is a test".to_string();
+        let s2 = "wokka wokka!!!".to_string();
+
+        let r = hamming_distance(s1.as_bytes(), s2.as_bytes());
+
+        assert_eq!(Ok(37), r);
+        assert_eq!(hamming_distance(&[0xff; 19], &[0x00; 19]), Ok(19 * 8));
+        assert!(hamming_distance(b"a", b"ab").is_err());
+        assert_eq!(edit_distance(s1.as_bytes(), s2.as_bytes()), r);
+    }
+
+    #[test]
+    fn test_score_plaintext() {
+        assert_eq!(score_plaintext(b""), 0.0);
+        // The space is one of the bonus characters.
+        assert_eq!(score_plaintext(b"Eta, XYZ!"), 6.0 - 0.5 + 2.0 + 3.0 - 0.5);
+        assert_eq!(score_plaintext(b"ETA"), score_plaintext(b"eta"));
+        // Invalid UTF-8 and other high bytes count for nothing.
+        assert_eq!(score_plaintext(b"e\xff\xc3t\x80a"), 6.0);
+    }
+
+    #[test]
+    fn test_frequency_helpers() {
+        let items = [1, 2, 2, 3, 2];
+        let freq = frequency_count(&items);
+
+        assert_eq!(most_frequent_item(&freq), Some((&&2, 3)));
+    }
+}
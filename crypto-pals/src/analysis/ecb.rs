@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EcbReport {
+    /// Blocks that duplicate an earlier block.
+    pub repeated_blocks: usize,
+    /// `repeated_blocks` as a share of all whole blocks, from 0 to 1.
+    pub score: f64,
+}
+
+/// A ciphertext's report, with its position in the input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EcbHit {
+    pub line: usize,
+    pub report: EcbReport,
+}
+
+/// Counts repeated blocks. Under ECB equal plaintext blocks encrypt alike;
+/// under any sound mode a repeat among random-looking blocks is
+/// vanishingly rare. A trailing partial block is ignored, and a zero
+/// `block_size` gives an empty report.
+pub fn detect_ecb(ciphertext: &[u8], block_size: usize) -> EcbReport {
+    if block_size == 0 {
+        return EcbReport {
+            repeated_blocks: 0,
+            score: 0.0,
+        };
+    }
+    let mut seen = HashSet::new();
+    let mut blocks = 0;
+    let mut repeated_blocks = 0;
+    for block in ciphertext.chunks_exact(block_size) {
+        blocks += 1;
+        if !seen.insert(block) {
+            repeated_blocks += 1;
+        }
+    }
+
+    let score = if blocks == 0 {
+        0.0
+    } else {
+        repeated_blocks as f64 / blocks as f64
+    };
+    EcbReport {
+        repeated_blocks,
+        score,
+    }
+}
+
+/// Reports every ciphertext, most ECB-like first.
+pub fn rank_ecb<T: AsRef<[u8]>>(ciphertexts: &[T], block_size: usize) -> Vec<EcbHit> {
+    let mut hits: Vec<_> = ciphertexts
+        .iter()
+        .enumerate()
+        .map(|(line, ciphertext)| EcbHit {
+            line,
+            report: detect_ecb(ciphertext.as_ref(), block_size),
+        })
+        .collect();
+    hits.sort_by(|a, b| b.report.score.total_cmp(&a.report.score));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{aes::Aes128, analysis::ecb::*, modes::Cbc, modes::Ecb};
+
+    #[test]
+    fn test_detect_ecb() {
+        let plaintext = b"sixteen byte blk".repeat(4);
+        let mut ecb = plaintext.clone();
+        Ecb::new(Aes128::new(b"YELLOW SUBMARINE"))
+            .encrypt(&mut ecb)
+            .unwrap();
+        let mut cbc = plaintext.clone();
+        Cbc::new(Aes128::new(b"YELLOW SUBMARINE"), &[7; 16])
+            .unwrap()
+            .encrypt(&mut cbc)
+            .unwrap();
+
+        assert_eq!(
+            detect_ecb(&ecb, 16),
+            EcbReport {
+                repeated_blocks: 3,
+                score: 0.75
+            }
+        );
+        assert_eq!(detect_ecb(&cbc, 16).repeated_blocks, 0);
+        assert_eq!(detect_ecb(b"short", 16).score, 0.0);
+
+        let hits = rank_ecb(&[&cbc, &ecb], 16);
+        assert_eq!(hits[0].line, 1);
+        assert_eq!(hits[1].report.score, 0.0);
+
+        let empty = EcbReport {
+            repeated_blocks: 0,
+            score: 0.0,
+        };
+        assert_eq!(detect_ecb(&ecb, 0), empty);
+        assert!(rank_ecb(&[&ecb], 0).iter().all(|hit| hit.report == empty));
+    }
+}
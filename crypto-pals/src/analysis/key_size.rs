@@ -0,0 +1,266 @@
+use crate::{
+    analysis::{Scorer, crack_columns, hamming_distance},
+    block::{reassemble_text, transpose_blocks},
+};
+
+/// Index of coincidence of ASCII English prose, spaces and case included.
+pub const ENGLISH_IOC: f64 = 0.066;
+
+/// Index of coincidence of uniformly random bytes.
+const RANDOM_IOC: f64 = 1.0 / 256.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeySizeMethod {
+    /// Mean bit distance between the first `blocks` key-sized blocks.
+    #[default]
+    Hamming,
+    /// Mean index of coincidence of the transposed columns, compared with the
+    /// plaintext's expected value.
+    IndexOfCoincidence,
+    /// The Friedman test's single estimate from the whole ciphertext; sizes
+    /// are ranked by their distance from it.
+    Friedman,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeySizeConfig {
+    pub method: KeySizeMethod,
+    pub min_size: usize,
+    pub max_size: usize,
+    /// Blocks compared pairwise by [`KeySizeMethod::Hamming`].
+    pub blocks: usize,
+    /// How many sizes to return, best first.
+    pub top_k: usize,
+    /// Expected index of coincidence of the plaintext.
+    pub plaintext_ioc: f64,
+}
+
+impl KeySizeConfig {
+    pub const DEFAULT: KeySizeConfig = KeySizeConfig {
+        method: KeySizeMethod::Hamming,
+        min_size: 2,
+        max_size: 40,
+        blocks: 8,
+        top_k: 3,
+        plaintext_ioc: ENGLISH_IOC,
+    };
+
+    pub const fn with_method(self, method: KeySizeMethod) -> KeySizeConfig {
+        KeySizeConfig { method, ..self }
+    }
+
+    pub const fn with_range(self, min_size: usize, max_size: usize) -> KeySizeConfig {
+        KeySizeConfig {
+            min_size,
+            max_size,
+            ..self
+        }
+    }
+
+    pub const fn with_blocks(self, blocks: usize) -> KeySizeConfig {
+        KeySizeConfig { blocks, ..self }
+    }
+
+    pub const fn with_top_k(self, top_k: usize) -> KeySizeConfig {
+        KeySizeConfig { top_k, ..self }
+    }
+
+    pub const fn with_plaintext_ioc(self, plaintext_ioc: f64) -> KeySizeConfig {
+        KeySizeConfig {
+            plaintext_ioc,
+            ..self
+        }
+    }
+}
+
+impl Default for KeySizeConfig {
+    fn default() -> Self {
+        KeySizeConfig::DEFAULT
+    }
+}
+
+/// A key size and its normalized distance; lower is more likely.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeySize {
+    pub key_size: usize,
+    pub distance: f64,
+}
+
+/// Ranks the sizes in the configured range, returning at most `top_k`.
+/// Sizes too large for the ciphertext to be measured are skipped.
+pub fn estimate_key_sizes(raw: &[u8], config: KeySizeConfig) -> Vec<KeySize> {
+    let friedman = friedman_estimate(raw, config.plaintext_ioc);
+    let mut sizes: Vec<_> = (config.min_size.max(1)..=config.max_size)
+        .filter_map(|key_size| {
+            let distance = match config.method {
+                KeySizeMethod::Hamming => hamming_score(raw, key_size, config.blocks)?,
+                KeySizeMethod::IndexOfCoincidence => {
+                    // Each column needs a couple of bytes for a coincidence.
+                    if raw.len() < 2 * key_size {
+                        return None;
+                    }
+                    let columns = transpose_blocks(raw, key_size);
+                    let mean = columns.iter().map(|c| index_of_coincidence(c)).sum::<f64>()
+                        / key_size as f64;
+                    (mean - config.plaintext_ioc).abs() / config.plaintext_ioc
+                }
+                KeySizeMethod::Friedman => {
+                    if raw.len() < key_size {
+                        return None;
+                    }
+                    (key_size as f64 - friedman?).abs() / key_size as f64
+                }
+            };
+            Some(KeySize { key_size, distance })
+        })
+        .collect();
+
+    sizes.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    sizes.truncate(config.top_k);
+    sizes
+}
+
+/// Cracks the ciphertext at each estimated size and keeps the one whose
+/// plaintext scores best. A key that merely repeats a shorter one is
+/// reported at the shorter size.
+pub fn select_key_size(raw: &[u8], config: KeySizeConfig, scorer: &dyn Scorer) -> Option<usize> {
+    estimate_key_sizes(raw, config)
+        .into_iter()
+        .map(|size| {
+            let (key, columns) = crack_columns(transpose_blocks(raw, size.key_size), scorer);
+            (
+                smallest_period(&key),
+                scorer.score(&reassemble_text(&columns)),
+            )
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(key_size, _)| key_size)
+}
+
+/// Probability that two bytes drawn from `bytes` are equal.
+pub fn index_of_coincidence(bytes: &[u8]) -> f64 {
+    if bytes.len() < 2 {
+        return 0.0;
+    }
+    let mut counts = [0u64; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+    let pairs: u64 = counts.iter().map(|&n| n * n.saturating_sub(1)).sum();
+    let n = bytes.len() as f64;
+    pairs as f64 / (n * (n - 1.0))
+}
+
+/// The Friedman test with its small-sample correction,
+/// `(κp - κr) n / ((n - 1) κo - n κr + κp)`. `None` when the ciphertext is
+/// too flat for a meaningful estimate.
+///
+/// The random-text baseline assumes key bytes are spread over all 256
+/// values; printable keys leave more coincidences behind, so the estimate
+/// runs low for them.
+pub fn friedman_estimate(raw: &[u8], plaintext_ioc: f64) -> Option<f64> {
+    let n = raw.len() as f64;
+    let observed = index_of_coincidence(raw);
+    let denominator = (n - 1.0) * observed - n * RANDOM_IOC + plaintext_ioc;
+    (observed > RANDOM_IOC && denominator > 0.0)
+        .then(|| (plaintext_ioc - RANDOM_IOC) * n / denominator)
+}
+
+/// Mean differing bits per byte over every pair of the first `blocks` blocks.
+fn hamming_score(raw: &[u8], key_size: usize, blocks: usize) -> Option<f64> {
+    let blocks: Vec<_> = raw.chunks_exact(key_size).take(blocks).collect();
+    if blocks.len() < 2 {
+        return None;
+    }
+
+    let mut total = 0;
+    let mut pairs = 0;
+    for (i, b1) in blocks.iter().enumerate() {
+        for b2 in &blocks[i + 1..] {
+            total += hamming_distance(b1, b2).expect("equal block sizes");
+            pairs += 1;
+        }
+    }
+    Some(total as f64 / pairs as f64 / key_size as f64)
+}
+
+pub(crate) fn smallest_period<T: PartialEq>(key: &[T]) -> usize {
+    (1..key.len())
+        .find(|&p| key.len().is_multiple_of(p) && key.iter().zip(&key[p..]).all(|(a, b)| a == b))
+        .unwrap_or(key.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::{key_size::*, scoring::ChiSquared},
+        xor::rolling_xor,
+    };
+
+    const PLAINTEXT: &[u8] =
+        b"It was the best of times, it was the worst of times, it was the age of \
+wisdom, it was the age of foolishness, it was the epoch of belief, it was the epoch of \
+incredulity, it was the season of Light, it was the season of Darkness, it was the spring of \
+hope, it was the winter of despair, we had everything before us, we had nothing before us, we \
+were all going direct to Heaven, we were all going direct the other way.";
+
+    #[test]
+    fn test_index_of_coincidence() {
+        assert_eq!(index_of_coincidence(b"aaaa"), 1.0);
+        assert_eq!(index_of_coincidence(b"abcd"), 0.0);
+        assert_eq!(index_of_coincidence(b"a"), 0.0);
+        assert!(index_of_coincidence(PLAINTEXT) > 0.05);
+    }
+
+    #[test]
+    fn test_methods_rank_true_size() {
+        let ciphertext = rolling_xor(PLAINTEXT, b"DICKENS").unwrap();
+
+        for method in [KeySizeMethod::Hamming, KeySizeMethod::IndexOfCoincidence] {
+            let config = KeySizeConfig::DEFAULT
+                .with_method(method)
+                .with_range(2, 20)
+                .with_top_k(5);
+            let sizes = estimate_key_sizes(&ciphertext, config);
+            assert_eq!(sizes.len(), 5);
+            assert!(sizes.windows(2).all(|w| w[0].distance <= w[1].distance));
+            assert!(
+                sizes.iter().any(|s| s.key_size % 7 == 0),
+                "{method:?}: {sizes:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_friedman() {
+        let key = [0x9f, 0x13, 0xc4, 0x5a, 0x71, 0xe8, 0x2b];
+        let ciphertext = rolling_xor(PLAINTEXT, &key).unwrap();
+
+        let estimate = friedman_estimate(&ciphertext, ENGLISH_IOC).unwrap();
+        assert!((5.0..9.0).contains(&estimate), "{estimate}");
+        let config = KeySizeConfig::DEFAULT.with_method(KeySizeMethod::Friedman);
+        assert_eq!(
+            estimate_key_sizes(&ciphertext, config)[0].key_size,
+            estimate.round() as usize
+        );
+        assert_eq!(friedman_estimate(&[0u8, 1, 2, 3], ENGLISH_IOC), None);
+    }
+
+    #[test]
+    fn test_select_key_size() {
+        let ciphertext = rolling_xor(PLAINTEXT, b"DICKENS").unwrap();
+        let config = KeySizeConfig::DEFAULT
+            .with_method(KeySizeMethod::IndexOfCoincidence)
+            .with_range(2, 20)
+            .with_top_k(5);
+        assert_eq!(select_key_size(&ciphertext, config, &ChiSquared), Some(7));
+        assert_eq!(select_key_size(b"", config, &ChiSquared), None);
+    }
+
+    #[test]
+    fn test_smallest_period() {
+        assert_eq!(smallest_period(b"abcabc"), 3);
+        assert_eq!(smallest_period(b"abcab"), 5);
+        assert_eq!(smallest_period(b"aaaa"), 1);
+    }
+}
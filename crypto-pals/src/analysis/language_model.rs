@@ -0,0 +1,351 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+};
+
+use crate::analysis::Scorer;
+
+const MAGIC: &str = "crypto-pals-lm 1";
+
+/// A byte-level interpolated trigram model. Training only counts; the
+/// log-probability tables are rebuilt from the counts whenever a model is
+/// trained or loaded, so the file format stays small and inspectable.
+#[derive(Debug, Clone)]
+pub struct LanguageModel {
+    unigrams: Box<[u64; 256]>,
+    bigrams: Vec<u64>,
+    trigrams: HashMap<[u8; 3], u64>,
+    total: u64,
+
+    unigram_lp: Box<[f64; 256]>,
+    bigram_lp: Vec<f64>,
+    trigram_lp: HashMap<[u8; 3], f64>,
+}
+
+impl LanguageModel {
+    /// Weight of the trigram estimate when its context has been seen.
+    const TRIGRAM_WEIGHT: f64 = 0.6;
+    /// Weight of the bigram estimate over the add-one unigram estimate.
+    const BIGRAM_WEIGHT: f64 = 0.7;
+
+    pub fn new() -> Self {
+        let mut model = LanguageModel {
+            unigrams: Box::new([0; 256]),
+            bigrams: vec![0; 256 * 256],
+            trigrams: HashMap::new(),
+            total: 0,
+            unigram_lp: Box::new([0.0; 256]),
+            bigram_lp: vec![0.0; 256 * 256],
+            trigram_lp: HashMap::new(),
+        };
+        model.rebuild();
+        model
+    }
+
+    pub fn train(text: &[u8]) -> Self {
+        let mut model = LanguageModel::new();
+        model.update(text);
+        model
+    }
+
+    pub fn train_file(path: &str) -> io::Result<Self> {
+        let mut text = Vec::new();
+        File::open(path)?.read_to_end(&mut text)?;
+        Ok(LanguageModel::train(&text))
+    }
+
+    /// Adds `text` to the counts and refreshes the tables.
+    pub fn update(&mut self, text: &[u8]) {
+        self.add_counts(text);
+        self.rebuild();
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        LanguageModel::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// One count per line: `u c n`, `b a c n` or `t a b c n`, bytes in decimal.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "{MAGIC}")?;
+        for (c, &n) in self.unigrams.iter().enumerate().filter(|(_, n)| **n > 0) {
+            writeln!(w, "u {c} {n}")?;
+        }
+        for (i, &n) in self.bigrams.iter().enumerate().filter(|(_, n)| **n > 0) {
+            writeln!(w, "b {} {} {n}", i >> 8, i & 0xff)?;
+        }
+        let mut trigrams: Vec<_> = self.trigrams.iter().collect();
+        trigrams.sort();
+        for ([a, b, c], n) in trigrams {
+            writeln!(w, "t {a} {b} {c} {n}")?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: BufRead>(r: R) -> io::Result<Self> {
+        let invalid = |line: usize, msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("language model line {}: {msg}", line + 1),
+            )
+        };
+
+        let mut model = LanguageModel::new();
+        let mut lines = r.lines().enumerate();
+        match lines.next() {
+            Some((_, Ok(header))) if header == MAGIC => {}
+            Some((_, Err(e))) => return Err(e),
+            _ => return Err(invalid(0, "missing header")),
+        }
+
+        let mut seen_unigrams = [false; 256];
+        let mut seen_bigrams = vec![false; 256 * 256];
+        for (i, line) in lines {
+            let line = line?;
+            let mut fields = line.split_ascii_whitespace();
+            let kind = fields.next();
+            let nums = fields
+                .map(|f| f.parse::<u64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid(i, "bad number"))?;
+            let byte = |n: u64| u8::try_from(n).map_err(|_| invalid(i, "byte out of range"));
+
+            let duplicate = match (kind, nums.as_slice()) {
+                (None, []) => false,
+                (Some("u"), &[c, n]) => {
+                    let c = byte(c)? as usize;
+                    model.unigrams[c] = n;
+                    std::mem::replace(&mut seen_unigrams[c], true)
+                }
+                (Some("b"), &[a, c, n]) => {
+                    let i = (byte(a)? as usize) << 8 | byte(c)? as usize;
+                    model.bigrams[i] = n;
+                    std::mem::replace(&mut seen_bigrams[i], true)
+                }
+                (Some("t"), &[a, b, c, n]) => model
+                    .trigrams
+                    .insert([byte(a)?, byte(b)?, byte(c)?], n)
+                    .is_some(),
+                _ => return Err(invalid(i, "unrecognised record")),
+            };
+            if duplicate {
+                return Err(invalid(i, "duplicate record"));
+            }
+        }
+
+        // Each count must fit inside the count of its context, or the
+        // estimates in `rebuild` divide by zero or exceed 1.
+        let malformed = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        for (i, &n) in model.bigrams.iter().enumerate() {
+            if n > model.unigrams[i >> 8] {
+                return Err(malformed(format!(
+                    "bigram {} {} exceeds its unigram count",
+                    i >> 8,
+                    i & 0xff
+                )));
+            }
+        }
+        for (&[a, b, c], &n) in &model.trigrams {
+            let context = model.bigrams[(a as usize) << 8 | b as usize];
+            if context == 0 || n > context {
+                return Err(malformed(format!(
+                    "trigram {a} {b} {c} exceeds its bigram count"
+                )));
+            }
+        }
+        model.total = model
+            .unigrams
+            .iter()
+            .try_fold(0u64, |total, &n| total.checked_add(n))
+            .ok_or_else(|| malformed("unigram counts overflow".to_string()))?;
+        model.rebuild();
+        Ok(model)
+    }
+
+    /// `ln P(c | a, b)`; pass fewer context bytes at the start of a text.
+    pub fn log_prob(&self, context: &[u8], c: u8) -> f64 {
+        match *context {
+            [.., a, b] => {
+                if let Some(&lp) = self.trigram_lp.get(&[a, b, c]) {
+                    return lp;
+                }
+                let bigram_lp = self.bigram_lp[(b as usize) << 8 | c as usize];
+                if self.bigrams[(a as usize) << 8 | b as usize] > 0 {
+                    (1.0 - Self::TRIGRAM_WEIGHT).ln() + bigram_lp
+                } else {
+                    bigram_lp
+                }
+            }
+            [b] => self.bigram_lp[(b as usize) << 8 | c as usize],
+            [] => self.unigram_lp[c as usize],
+        }
+    }
+
+    fn add_counts(&mut self, text: &[u8]) {
+        for &c in text {
+            self.unigrams[c as usize] += 1;
+        }
+        for pair in text.windows(2) {
+            self.bigrams[(pair[0] as usize) << 8 | pair[1] as usize] += 1;
+        }
+        for triple in text.windows(3) {
+            *self
+                .trigrams
+                .entry([triple[0], triple[1], triple[2]])
+                .or_insert(0) += 1;
+        }
+        self.total += text.len() as u64;
+    }
+
+    fn rebuild(&mut self) {
+        let total = self.total as f64 + 256.0;
+        for (lp, &n) in self.unigram_lp.iter_mut().zip(self.unigrams.iter()) {
+            *lp = ((n as f64 + 1.0) / total).ln();
+        }
+
+        // Bigram counts are indexed by their first byte's unigram count,
+        // which over-counts the final byte of the text by at most one.
+        for b in 0..256 {
+            let context = self.unigrams[b] as f64;
+            for c in 0..256 {
+                let i = b << 8 | c;
+                let unigram = self.unigram_lp[c].exp();
+                let p = if context > 0.0 {
+                    Self::BIGRAM_WEIGHT * self.bigrams[i] as f64 / context
+                        + (1.0 - Self::BIGRAM_WEIGHT) * unigram
+                } else {
+                    unigram
+                };
+                self.bigram_lp[i] = p.ln();
+            }
+        }
+
+        self.trigram_lp = self
+            .trigrams
+            .iter()
+            .map(|(&[a, b, c], &n)| {
+                let context = self.bigrams[(a as usize) << 8 | b as usize] as f64;
+                let bigram = self.bigram_lp[(b as usize) << 8 | c as usize].exp();
+                let p = Self::TRIGRAM_WEIGHT * n as f64 / context
+                    + (1.0 - Self::TRIGRAM_WEIGHT) * bigram;
+                ([a, b, c], p.ln())
+            })
+            .collect();
+    }
+}
+
+impl Default for LanguageModel {
+    fn default() -> Self {
+        LanguageModel::new()
+    }
+}
+
+/// Mean per-byte log-probability.
+impl Scorer for LanguageModel {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        if bytes.is_empty() {
+            return f64::MIN;
+        }
+        let total: f64 = (0..bytes.len())
+            .map(|i| self.log_prob(&bytes[i.saturating_sub(2)..i], bytes[i]))
+            .sum();
+        total / bytes.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Scorer, language_model::*};
+    use crate::xor::xor_with_key;
+
+    const JSON_CORPUS: &[u8] = br#"{"id": 1, "name": "alpha", "tags": ["x", "y"], "active": true}
+{"id": 2, "name": "beta", "tags": [], "active": false}
+{"id": 3, "name": "gamma", "tags": ["z"], "active": true, "owner": {"id": 9}}
+"#;
+
+    fn best_key(ciphertext: &[u8], scorer: &dyn Scorer) -> u8 {
+        (0..=255u8)
+            .max_by(|&a, &b| {
+                scorer
+                    .score(&xor_with_key(ciphertext, a))
+                    .total_cmp(&scorer.score(&xor_with_key(ciphertext, b)))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cracks_domain_text() {
+        let model = LanguageModel::train(JSON_CORPUS);
+        let plaintext = br#"{"id": 7, "name": "delta", "active": false}"#;
+
+        for key in [0x01, 0x20, 0x5a, 0xc3] {
+            assert_eq!(best_key(&xor_with_key(plaintext, key), &model), key);
+        }
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let model = LanguageModel::train(b"abababab");
+        assert!(model.log_prob(b"ab", b'a') > model.log_prob(b"ab", b'b'));
+        assert!(model.log_prob(b"a", b'b') > model.log_prob(b"a", b'a'));
+        assert!(model.log_prob(b"", b'a') > model.log_prob(b"", b'z'));
+        assert!(model.log_prob(b"zz", b'a') < 0.0);
+
+        let untrained = LanguageModel::new();
+        assert_eq!(untrained.score(b"a"), (1.0f64 / 256.0).ln());
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        let model = LanguageModel::train(JSON_CORPUS);
+
+        let mut buf = Vec::new();
+        model.write_to(&mut buf).unwrap();
+        let loaded = LanguageModel::read_from(buf.as_slice()).unwrap();
+        assert_eq!(loaded.score(JSON_CORPUS), model.score(JSON_CORPUS));
+
+        let path = std::env::temp_dir().join(format!("crypto-pals-lm-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        model.save(path).unwrap();
+        let loaded = LanguageModel::load(path);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.unwrap().score(b"{\"id\""), model.score(b"{\"id\""));
+    }
+
+    #[test]
+    fn test_malformed_model() {
+        for bad in [
+            "",
+            "not a model\n",
+            "crypto-pals-lm 1\nu 300 1\n",
+            "crypto-pals-lm 1\nq 1 2\n",
+            // An orphan trigram: its `a b` bigram was never seen.
+            "crypto-pals-lm 1\nu 97 2\nu 98 1\nt 97 98 99 1\n",
+            "crypto-pals-lm 1\nu 97 2\nu 98 1\nb 97 98 1\nt 97 98 99 2\n",
+            "crypto-pals-lm 1\nu 97 1\nb 97 98 3\n",
+            "crypto-pals-lm 1\nu 97 18446744073709551615\nu 98 1\n",
+            // Duplicate records, which would silently overwrite each other.
+            "crypto-pals-lm 1\nu 97 2\nu 97 3\n",
+            "crypto-pals-lm 1\nu 97 2\nb 97 97 1\nb 97 97 1\n",
+            "crypto-pals-lm 1\nu 97 3\nb 97 97 2\nt 97 97 97 1\nt 97 97 97 1\n",
+        ] {
+            let err = LanguageModel::read_from(bad.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_update_matches_train() {
+        let mut model = LanguageModel::train(b"hello ");
+        model.update(b"world");
+        let whole = LanguageModel::train(b"hello world");
+        // Only the " w" bigram across the boundary differs.
+        assert!((model.score(b"hello") - whole.score(b"hello")).abs() < 0.1);
+    }
+}
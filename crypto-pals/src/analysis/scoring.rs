@@ -0,0 +1,293 @@
+use crate::analysis::score_plaintext;
+
+/// Rates how much a candidate decryption looks like plaintext. Higher is
+/// better; scores are only comparable between outputs of the same scorer.
+pub trait Scorer {
+    fn score(&self, bytes: &[u8]) -> f64;
+}
+
+impl<F: Fn(&[u8]) -> f64> Scorer for F {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        self(bytes)
+    }
+}
+
+/// English letter frequencies in percent, `a` to `z`.
+pub const ENGLISH_UNIGRAMS: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// The most common English letter bigrams, in percent of all bigrams.
+pub const ENGLISH_BIGRAMS: [(&[u8; 2], f64); 42] = [
+    (b"th", 3.56),
+    (b"he", 3.07),
+    (b"in", 2.43),
+    (b"er", 2.05),
+    (b"an", 1.99),
+    (b"re", 1.85),
+    (b"on", 1.76),
+    (b"at", 1.49),
+    (b"en", 1.45),
+    (b"nd", 1.35),
+    (b"ti", 1.34),
+    (b"es", 1.34),
+    (b"or", 1.28),
+    (b"te", 1.20),
+    (b"of", 1.17),
+    (b"ed", 1.17),
+    (b"is", 1.13),
+    (b"it", 1.12),
+    (b"al", 1.09),
+    (b"ar", 1.07),
+    (b"st", 1.05),
+    (b"to", 1.04),
+    (b"nt", 1.04),
+    (b"ng", 0.95),
+    (b"se", 0.93),
+    (b"ha", 0.93),
+    (b"as", 0.87),
+    (b"ou", 0.87),
+    (b"io", 0.83),
+    (b"le", 0.83),
+    (b"ve", 0.83),
+    (b"co", 0.79),
+    (b"me", 0.79),
+    (b"de", 0.76),
+    (b"hi", 0.76),
+    (b"ri", 0.73),
+    (b"ro", 0.73),
+    (b"ic", 0.70),
+    (b"ne", 0.69),
+    (b"ea", 0.69),
+    (b"ra", 0.69),
+    (b"ce", 0.65),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Letter(u8),
+    Space,
+    /// Digits, punctuation and line breaks.
+    Other,
+    /// Control characters and anything outside ASCII.
+    Invalid,
+}
+
+fn classify(b: u8) -> Class {
+    match b {
+        b'a'..=b'z' => Class::Letter(b - b'a'),
+        b'A'..=b'Z' => Class::Letter(b - b'A'),
+        b' ' => Class::Space,
+        b'\n' | b'\r' | b'\t' => Class::Other,
+        _ if b.is_ascii_graphic() => Class::Other,
+        _ => Class::Invalid,
+    }
+}
+
+/// The original "etaoin shrdlu" bonus heuristic, see [`score_plaintext`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Heuristic;
+
+impl Scorer for Heuristic {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        score_plaintext(bytes) as f64
+    }
+}
+
+/// Share of bytes that are printable ASCII or common whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrintableRatio;
+
+impl Scorer for PrintableRatio {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        if bytes.is_empty() {
+            return 0.0;
+        }
+        let printable = bytes
+            .iter()
+            .filter(|&&b| classify(b) != Class::Invalid)
+            .count();
+        printable as f64 / bytes.len() as f64
+    }
+}
+
+/// Negated Pearson chi-squared statistic against English unigram
+/// frequencies, with spaces, other printables and invalid bytes as extra
+/// categories.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChiSquared;
+
+impl ChiSquared {
+    const SPACE: f64 = 0.17;
+    const OTHER: f64 = 0.0299;
+    const INVALID: f64 = 0.0001;
+    const LETTERS: f64 = 1.0 - Self::SPACE - Self::OTHER - Self::INVALID;
+}
+
+impl Scorer for ChiSquared {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        if bytes.is_empty() {
+            return f64::MIN;
+        }
+
+        let mut letters = [0usize; 26];
+        let (mut space, mut other, mut invalid) = (0, 0, 0);
+        for &b in bytes {
+            match classify(b) {
+                Class::Letter(i) => letters[i as usize] += 1,
+                Class::Space => space += 1,
+                Class::Other => other += 1,
+                Class::Invalid => invalid += 1,
+            }
+        }
+
+        let len = bytes.len() as f64;
+        let term = |observed: usize, p: f64| {
+            let expected = len * p;
+            (observed as f64 - expected).powi(2) / expected
+        };
+
+        let chi2 = letters
+            .iter()
+            .zip(ENGLISH_UNIGRAMS)
+            .map(|(&observed, pct)| term(observed, pct / 100.0 * Self::LETTERS))
+            .sum::<f64>()
+            + term(space, Self::SPACE)
+            + term(other, Self::OTHER)
+            + term(invalid, Self::INVALID);
+        -chi2
+    }
+}
+
+/// Mean log-probability of adjacent byte pairs under an English bigram
+/// model. Letter pairs use [`ENGLISH_BIGRAMS`], with the leftover mass
+/// spread over the unlisted pairs; the rest of the probability goes to word
+/// boundaries and, sparingly, to punctuation and digits.
+#[derive(Debug, Clone)]
+pub struct BigramLogLikelihood {
+    letter_pairs: Box<[f64; 26 * 26]>,
+    /// A word boundary next to each letter.
+    space_letter: [f64; 26],
+}
+
+impl BigramLogLikelihood {
+    /// Rough share of each pair shape in English prose.
+    const LETTER_LETTER: f64 = 0.70;
+    const SPACE_LETTER: f64 = 0.13;
+    const LETTER_OTHER: f64 = 0.015;
+    const SPACE_OTHER: f64 = 0.02;
+    const OTHER_OTHER: f64 = 0.005;
+    const SPACE_SPACE: f64 = 1e-4;
+    const INVALID: f64 = 1e-7;
+    /// Distinct printable non-letter, non-space bytes.
+    const OTHERS: f64 = 32.0;
+
+    pub fn new() -> Self {
+        let listed: f64 = ENGLISH_BIGRAMS.iter().map(|(_, pct)| pct / 100.0).sum();
+        let floor = (1.0 - listed) / (26 * 26 - ENGLISH_BIGRAMS.len()) as f64;
+
+        let mut letter_pairs = Box::new([(floor * Self::LETTER_LETTER).ln(); 26 * 26]);
+        for (pair, pct) in ENGLISH_BIGRAMS {
+            let i = (pair[0] - b'a') as usize * 26 + (pair[1] - b'a') as usize;
+            letter_pairs[i] = (pct / 100.0 * Self::LETTER_LETTER).ln();
+        }
+        let space_letter = ENGLISH_UNIGRAMS.map(|pct| (pct / 100.0 * Self::SPACE_LETTER).ln());
+
+        BigramLogLikelihood {
+            letter_pairs,
+            space_letter,
+        }
+    }
+
+    fn pair(&self, a: Class, b: Class) -> f64 {
+        match (a, b) {
+            (Class::Invalid, _) | (_, Class::Invalid) => Self::INVALID.ln(),
+            (Class::Letter(a), Class::Letter(b)) => self.letter_pairs[a as usize * 26 + b as usize],
+            (Class::Space, Class::Letter(l)) | (Class::Letter(l), Class::Space) => {
+                self.space_letter[l as usize]
+            }
+            (Class::Space, Class::Space) => Self::SPACE_SPACE.ln(),
+            (Class::Letter(_), Class::Other) | (Class::Other, Class::Letter(_)) => {
+                (Self::LETTER_OTHER / (26.0 * Self::OTHERS)).ln()
+            }
+            (Class::Space, Class::Other) | (Class::Other, Class::Space) => {
+                (Self::SPACE_OTHER / Self::OTHERS).ln()
+            }
+            (Class::Other, Class::Other) => {
+                (Self::OTHER_OTHER / (Self::OTHERS * Self::OTHERS)).ln()
+            }
+        }
+    }
+}
+
+impl Default for BigramLogLikelihood {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scorer for BigramLogLikelihood {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        if bytes.len() < 2 {
+            return PrintableRatio.score(bytes).ln();
+        }
+
+        let total: f64 = bytes
+            .windows(2)
+            .map(|pair| self.pair(classify(pair[0]), classify(pair[1])))
+            .sum();
+        total / (bytes.len() - 1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::scoring::*;
+    use crate::xor::xor_with_key;
+
+    const ENGLISH: &[u8] = b"Now that the party is jumping, and the bass kicks in";
+
+    fn scorers() -> Vec<Box<dyn Scorer>> {
+        vec![
+            Box::new(Heuristic),
+            Box::new(PrintableRatio),
+            Box::new(ChiSquared),
+            Box::new(BigramLogLikelihood::new()),
+        ]
+    }
+
+    #[test]
+    fn test_english_beats_xored() {
+        for scorer in scorers() {
+            let english = scorer.score(ENGLISH);
+            for key in [0x01, 0x20, 0x55, 0xff] {
+                assert!(
+                    english >= scorer.score(&xor_with_key(ENGLISH, key)),
+                    "key {key:#x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_chi_squared_prefers_english_distribution() {
+        let chi = ChiSquared;
+        assert!(chi.score(b"the quick brown fox") > chi.score(b"zzqx jjkv qqzx xvjk"));
+        assert!(chi.score(b"hello world") > chi.score(b"hello\x00world"));
+        assert_eq!(chi.score(b""), f64::MIN);
+    }
+
+    #[test]
+    fn test_bigram_prefers_common_pairs() {
+        let bigram = BigramLogLikelihood::new();
+        assert!(bigram.score(b"there") > bigram.score(b"qxzjv"));
+        assert!(bigram.score(b"then") > bigram.score(b"nhet"));
+    }
+
+    #[test]
+    fn test_closure_scorer() {
+        let length = |bytes: &[u8]| bytes.len() as f64;
+        assert_eq!(length.score(b"four"), 4.0);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{analysis::Scorer, xor::xor_with_key};
+
+/// One key tried against a ciphertext.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub key: u8,
+    pub score: f64,
+    pub plaintext: Vec<u8>,
+}
+
+/// A candidate found in one of several ciphertexts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub line: usize,
+    pub candidate: Candidate,
+}
+
+/// Tries every key and returns the `n` best, highest score first. Ties keep
+/// the lower key first.
+pub fn crack_single_byte_xor(ciphertext: &[u8], scorer: &dyn Scorer, n: usize) -> Vec<Candidate> {
+    let mut candidates: Vec<_> = (0..=255)
+        .map(|key| {
+            let plaintext = xor_with_key(ciphertext, key);
+            Candidate {
+                key,
+                score: scorer.score(&plaintext),
+                plaintext,
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(n);
+    candidates
+}
+
+/// Cracks every line and returns the `n` best candidates across all of them.
+pub fn detect_single_byte_xor<T: AsRef<[u8]>>(
+    lines: &[T],
+    scorer: &dyn Scorer,
+    n: usize,
+) -> Vec<Hit> {
+    let mut hits: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(line, ciphertext)| {
+            crack_single_byte_xor(ciphertext.as_ref(), scorer, n)
+                .into_iter()
+                .map(move |candidate| Hit { line, candidate })
+        })
+        .collect();
+    hits.sort_by(|a, b| b.candidate.score.total_cmp(&a.candidate.score));
+    hits.truncate(n);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{scoring::ChiSquared, single_byte::*};
+
+    #[test]
+    fn test_crack_ranks_candidates() {
+        let plaintext = b"The quick brown fox jumps over the lazy dog";
+        let ciphertext = xor_with_key(plaintext, 0x42);
+
+        let top = crack_single_byte_xor(&ciphertext, &ChiSquared, 3);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].key, 0x42);
+        assert_eq!(top[0].plaintext, plaintext);
+        assert!(top.windows(2).all(|w| w[0].score >= w[1].score));
+
+        assert_eq!(
+            crack_single_byte_xor(&ciphertext, &ChiSquared, 1000).len(),
+            256
+        );
+        assert!(crack_single_byte_xor(&ciphertext, &ChiSquared, 0).is_empty());
+    }
+
+    #[test]
+    fn test_detect_finds_line() {
+        let lines = [
+            vec![0x13, 0x7f, 0xa0, 0x55, 0x01, 0xee, 0x3c, 0x90, 0x4d, 0x21],
+            xor_with_key(b"attack at dawn", 0x17),
+            vec![0xff; 14],
+        ];
+
+        let hits = detect_single_byte_xor(&lines, &ChiSquared, 2);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].line, 1);
+        assert_eq!(hits[0].candidate.key, 0x17);
+        assert_eq!(hits[0].candidate.plaintext, b"attack at dawn");
+    }
+}
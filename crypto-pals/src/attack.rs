@@ -0,0 +1,5 @@
+pub mod bitflip;
+pub mod ctr_edit;
+pub mod ecb_byte_at_a_time;
+pub mod padding_oracle;
+pub mod profile;
@@ -0,0 +1,269 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    aes::{self, Aes128},
+    attack::profile::CookieFormat,
+    modes::{Cbc, Ctr},
+    padding::{pkcs7_pad, pkcs7_unpad_block},
+    random::{random_array, random_bytes},
+    xor::{apply_keystream, fixed_xor, xor_into},
+};
+
+const PREFIX: &str = "comment1=cooking%20MCs;userdata=";
+const SUFFIX: &str = ";comment2=%20like%20a%20pound%20of%20bacon";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitflipError {
+    /// The known and desired plaintexts differ in length.
+    LengthMismatch { known: usize, desired: usize },
+    /// The edit runs past the end of the ciphertext or of its block.
+    OutOfRange,
+    /// CBC edits change the block before the target, so block 0 (whose
+    /// predecessor is the IV) cannot be edited through the ciphertext.
+    NoPrecedingBlock,
+}
+
+impl fmt::Display for BitflipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitflipError::LengthMismatch { known, desired } => {
+                write!(f, "known plaintext is {known} bytes, desired is {desired}")
+            }
+            BitflipError::OutOfRange => write!(f, "edit is out of range"),
+            BitflipError::NoPrecedingBlock => write!(f, "block 0 has no preceding block"),
+        }
+    }
+}
+
+impl Error for BitflipError {}
+
+fn delta(known_plain: &[u8], desired_plain: &[u8]) -> Result<Vec<u8>, BitflipError> {
+    fixed_xor(known_plain, desired_plain).map_err(|_| BitflipError::LengthMismatch {
+        known: known_plain.len(),
+        desired: desired_plain.len(),
+    })
+}
+
+/// Makes the start of CBC plaintext block `block_index` read `desired_plain`
+/// instead of `known_plain` by XORing their difference into the previous
+/// ciphertext block, which garbles that block's plaintext in turn.
+pub fn cbc_bitflip(
+    ciphertext: &[u8],
+    block_index: usize,
+    known_plain: &[u8],
+    desired_plain: &[u8],
+) -> Result<Vec<u8>, BitflipError> {
+    let delta = delta(known_plain, desired_plain)?;
+    if block_index == 0 {
+        return Err(BitflipError::NoPrecedingBlock);
+    }
+    if delta.len() > aes::BLOCK_LEN || (block_index + 1) * aes::BLOCK_LEN > ciphertext.len() {
+        return Err(BitflipError::OutOfRange);
+    }
+
+    let mut out = ciphertext.to_vec();
+    let start = (block_index - 1) * aes::BLOCK_LEN;
+    xor_into(&mut out[start..start + delta.len()], &delta).expect("equal lengths");
+    Ok(out)
+}
+
+/// The CTR counterpart: the plaintext at `offset` changes from
+/// `known_plain` to `desired_plain` and nothing else is disturbed.
+pub fn ctr_bitflip(
+    ciphertext: &[u8],
+    offset: usize,
+    known_plain: &[u8],
+    desired_plain: &[u8],
+) -> Result<Vec<u8>, BitflipError> {
+    let delta = delta(known_plain, desired_plain)?;
+    if offset + delta.len() > ciphertext.len() {
+        return Err(BitflipError::OutOfRange);
+    }
+
+    let mut out = ciphertext.to_vec();
+    xor_into(&mut out[offset..offset + delta.len()], &delta).expect("equal lengths");
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentMode {
+    Cbc,
+    Ctr,
+}
+
+#[derive(Debug, Clone)]
+enum CommentCipher {
+    Cbc(Cbc<Aes128>),
+    Ctr { cipher: Aes128, nonce: u64 },
+}
+
+/// Challenges 16 and 26: wraps quoted user data in fixed comments and
+/// encrypts it under a key and IV (or nonce) of its own.
+#[derive(Debug, Clone)]
+pub struct CommentService {
+    cipher: CommentCipher,
+}
+
+impl CommentService {
+    pub fn new(mode: CommentMode) -> Self {
+        let key = Aes128::new(&random_array());
+        let cipher = match mode {
+            CommentMode::Cbc => {
+                CommentCipher::Cbc(Cbc::new(key, &random_bytes(16)).expect("block-sized IV"))
+            }
+            CommentMode::Ctr => CommentCipher::Ctr {
+                cipher: key,
+                nonce: u64::from_le_bytes(random_array()),
+            },
+        };
+        CommentService { cipher }
+    }
+
+    pub fn mode(&self) -> CommentMode {
+        match self.cipher {
+            CommentCipher::Cbc(_) => CommentMode::Cbc,
+            CommentCipher::Ctr { .. } => CommentMode::Ctr,
+        }
+    }
+
+    /// `;` and `=` in `userdata` are percent-escaped so it cannot add fields.
+    pub fn encrypt(&self, userdata: &str) -> Vec<u8> {
+        let plaintext = format!(
+            "{PREFIX}{}{SUFFIX}",
+            CookieFormat::SEMICOLON.quote(userdata)
+        );
+        match &self.cipher {
+            CommentCipher::Cbc(cbc) => {
+                let mut buf = pkcs7_pad(plaintext.as_bytes(), aes::BLOCK_LEN);
+                cbc.encrypt(&mut buf).expect("padded input");
+                buf
+            }
+            CommentCipher::Ctr { cipher, nonce } => {
+                let mut buf = plaintext.into_bytes();
+                apply_keystream(&mut buf, Ctr::with_nonce(cipher.clone(), *nonce));
+                buf
+            }
+        }
+    }
+
+    /// Looks for an `admin=true` field. Fields are split on raw bytes, since
+    /// a tampered CBC block decrypts to garbage that is rarely UTF-8.
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        let mut buf = ciphertext.to_vec();
+        let plaintext = match &self.cipher {
+            CommentCipher::Cbc(cbc) => {
+                if cbc.decrypt(&mut buf).is_err() {
+                    return false;
+                }
+                match pkcs7_unpad_block(&buf, aes::BLOCK_LEN) {
+                    Ok(plaintext) => plaintext,
+                    Err(_) => return false,
+                }
+            }
+            CommentCipher::Ctr { cipher, nonce } => {
+                apply_keystream(&mut buf, Ctr::with_nonce(cipher.clone(), *nonce));
+                &buf
+            }
+        };
+        plaintext
+            .split(|&b| b == b';')
+            .any(|field| field == b"admin=true")
+    }
+}
+
+/// Grants admin from `encrypt` alone. The user data starts on a block
+/// boundary; in CBC a sacrificial block absorbs the edit.
+pub fn forge_admin_comment(encrypt: impl Fn(&str) -> Vec<u8>, mode: CommentMode) -> Vec<u8> {
+    const KNOWN: &[u8] = b"XadminXtrueX";
+    const DESIRED: &[u8] = b";admin=true;";
+    let known = std::str::from_utf8(KNOWN).expect("ASCII");
+
+    match mode {
+        CommentMode::Cbc => {
+            let userdata = format!("{}{known}", "A".repeat(aes::BLOCK_LEN));
+            let target = PREFIX.len() / aes::BLOCK_LEN + 1;
+            cbc_bitflip(&encrypt(&userdata), target, KNOWN, DESIRED)
+        }
+        CommentMode::Ctr => ctr_bitflip(&encrypt(known), PREFIX.len(), KNOWN, DESIRED),
+    }
+    .expect("edit lies inside the ciphertext")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        aes::Aes128,
+        attack::bitflip::*,
+        modes::{Cbc, CounterLayout},
+        xor::Keystream,
+    };
+
+    #[test]
+    fn test_quoting() {
+        for mode in [CommentMode::Cbc, CommentMode::Ctr] {
+            let service = CommentService::new(mode);
+            assert_eq!(service.mode(), mode);
+            assert!(!service.is_admin(&service.encrypt(";admin=true;")));
+            assert!(!service.is_admin(&service.encrypt("admin=true")));
+        }
+    }
+
+    #[test]
+    fn test_c16() {
+        let service = CommentService::new(CommentMode::Cbc);
+        let forged = forge_admin_comment(|s| service.encrypt(s), CommentMode::Cbc);
+        assert!(service.is_admin(&forged));
+    }
+
+    #[test]
+    fn test_c26() {
+        let service = CommentService::new(CommentMode::Ctr);
+        let forged = forge_admin_comment(|s| service.encrypt(s), CommentMode::Ctr);
+        assert!(service.is_admin(&forged));
+    }
+
+    #[test]
+    fn test_bitflip_helpers() {
+        let cbc = Cbc::new(Aes128::new(b"YELLOW SUBMARINE"), &[0; 16]).unwrap();
+        let mut ciphertext = b"block zero......block one.......block two.......".to_vec();
+        cbc.encrypt(&mut ciphertext).unwrap();
+
+        let mut flipped = cbc_bitflip(&ciphertext, 2, b"block two", b"BLOCK 2!!").unwrap();
+        cbc.decrypt(&mut flipped).unwrap();
+        assert_eq!(&flipped[..16], b"block zero......");
+        assert_ne!(&flipped[16..32], b"block one.......");
+        assert_eq!(&flipped[32..], b"BLOCK 2!!.......");
+
+        assert_eq!(
+            cbc_bitflip(&ciphertext, 0, b"a", b"b"),
+            Err(BitflipError::NoPrecedingBlock)
+        );
+        assert_eq!(
+            cbc_bitflip(&ciphertext, 3, b"a", b"b"),
+            Err(BitflipError::OutOfRange)
+        );
+        assert_eq!(
+            cbc_bitflip(&ciphertext, 1, b"ab", b"b"),
+            Err(BitflipError::LengthMismatch {
+                known: 2,
+                desired: 1
+            })
+        );
+
+        let mut ctr = Ctr::new(
+            Aes128::new(b"YELLOW SUBMARINE"),
+            &[0; 16],
+            CounterLayout::BigEndian128,
+        )
+        .unwrap();
+        let mut ciphertext = *b"pay alice $10";
+        ctr.clone().apply(&mut ciphertext);
+        let mut flipped = ctr_bitflip(&ciphertext, 4, b"alice", b"mallo").unwrap();
+        ctr.apply(&mut flipped);
+        assert_eq!(flipped, *b"pay mallo $10");
+        assert_eq!(
+            ctr_bitflip(&ciphertext, 10, b"$10!", b"$999"),
+            Err(BitflipError::OutOfRange)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    modes::{AesCtr, ModeError},
+    random::random_array,
+    xor::apply_keystream,
+};
+
+/// Challenge 25: holds a CTR ciphertext under a secret key and lets anyone
+/// rewrite part of it, as a disk-encryption layer might.
+#[derive(Debug, Clone)]
+pub struct EditService {
+    ctr: AesCtr,
+    ciphertext: Vec<u8>,
+}
+
+impl EditService {
+    pub fn new(plaintext: &[u8]) -> Self {
+        let ctr = AesCtr::new(&random_array(), u64::from_le_bytes(random_array()));
+        let mut ciphertext = plaintext.to_vec();
+        apply_keystream(&mut ciphertext, ctr.clone());
+        EditService { ctr, ciphertext }
+    }
+
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    pub fn edit(
+        &self,
+        ciphertext: &[u8],
+        offset: usize,
+        newtext: &[u8],
+    ) -> Result<Vec<u8>, ModeError> {
+        self.ctr.edit(ciphertext, offset, newtext)
+    }
+}
+
+/// Recovers the plaintext of `ciphertext` from an edit function that
+/// reuses its keystream. Editing the ciphertext in as new plaintext XORs
+/// the keystream back out, so one call returns the plaintext.
+pub fn recover_via_edit(
+    ciphertext: &[u8],
+    mut edit: impl FnMut(&[u8], usize, &[u8]) -> Vec<u8>,
+) -> Vec<u8> {
+    edit(ciphertext, 0, ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::{
+        aes::Aes128, attack::ctr_edit::*, encoding::open_b64, modes::Ecb, padding::pkcs7_unpad,
+        xor::fixed_xor,
+    };
+
+    #[test]
+    fn test_c25() {
+        // 25.txt is the same file as 7.txt.
+        let mut plaintext = Vec::new();
+        open_b64("./static/7.txt")
+            .and_then(|mut reader| reader.read_to_end(&mut plaintext))
+            .expect("decode 7.txt");
+        Ecb::new(Aes128::new(b"YELLOW SUBMARINE"))
+            .decrypt(&mut plaintext)
+            .unwrap();
+        let plaintext = pkcs7_unpad(&plaintext).unwrap();
+
+        let service = EditService::new(plaintext);
+        let ciphertext = service.ciphertext();
+        assert_ne!(ciphertext, plaintext);
+
+        let recovered = recover_via_edit(ciphertext, |ct, offset, newtext| {
+            service.edit(ct, offset, newtext).unwrap()
+        });
+        assert_eq!(recovered, plaintext);
+
+        // The slower route, zeros in for the raw keystream, agrees.
+        let keystream = service
+            .edit(ciphertext, 0, &vec![0; ciphertext.len()])
+            .unwrap();
+        assert_eq!(fixed_xor(ciphertext, &keystream).unwrap(), plaintext);
+    }
+}
@@ -0,0 +1,266 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    aes::{self, Aes128},
+    analysis::detect_ecb,
+    modes::Ecb,
+    oracle::Oracle,
+    padding::pkcs7_pad,
+    random::{random_array, random_bytes, random_in},
+};
+
+/// Longest block size tried before giving up.
+const MAX_BLOCK_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackError {
+    /// The ciphertext never grew by a whole block.
+    NoBlockSize,
+    /// Repeated input blocks did not give repeated ciphertext blocks.
+    NotEcb,
+    /// No filler length lined the input up on a block boundary, which
+    /// happens when the prefix changes between queries.
+    PrefixUnstable,
+    /// No guess matched the secret byte at this offset.
+    ByteNotFound { offset: usize },
+    /// The ciphertext lengths do not fit a prefix, a secret and a PKCS#7
+    /// pad.
+    LayoutMismatch,
+}
+
+impl fmt::Display for AttackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttackError::NoBlockSize => write!(f, "could not find the block size"),
+            AttackError::NotEcb => write!(f, "oracle does not encrypt with ECB"),
+            AttackError::PrefixUnstable => write!(f, "could not align with the prefix"),
+            AttackError::ByteNotFound { offset } => {
+                write!(f, "no guess matched the secret byte at offset {offset}")
+            }
+            AttackError::LayoutMismatch => {
+                write!(
+                    f,
+                    "ciphertext lengths do not fit prefix, secret and padding"
+                )
+            }
+        }
+    }
+}
+
+impl Error for AttackError {}
+
+/// What the attack learned about the oracle before decrypting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcbLayout {
+    pub block_size: usize,
+    pub prefix_len: usize,
+    pub secret_len: usize,
+}
+
+/// `AES-128-ECB(prefix || input || secret)` under a random key, with a
+/// PKCS#7 pad. The prefix stays the same for every query.
+#[derive(Debug, Clone)]
+pub struct EcbSecretOracle {
+    ecb: Ecb<Aes128>,
+    prefix: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl EcbSecretOracle {
+    /// Challenge 12: no prefix.
+    pub fn new(secret: &[u8]) -> Self {
+        EcbSecretOracle::with_prefix(&[], secret)
+    }
+
+    /// Challenge 14: between 0 and 64 random bytes of prefix.
+    pub fn with_random_prefix(secret: &[u8]) -> Self {
+        EcbSecretOracle::with_prefix(&random_bytes(random_in(0..=64)), secret)
+    }
+
+    pub fn with_prefix(prefix: &[u8], secret: &[u8]) -> Self {
+        EcbSecretOracle {
+            ecb: Ecb::new(Aes128::new(&random_array())),
+            prefix: prefix.to_vec(),
+            secret: secret.to_vec(),
+        }
+    }
+}
+
+impl Oracle for EcbSecretOracle {
+    fn query(&mut self, input: &[u8]) -> Vec<u8> {
+        let plaintext = [&self.prefix[..], input, &self.secret].concat();
+        let mut buf = pkcs7_pad(&plaintext, aes::BLOCK_LEN);
+        self.ecb.encrypt(&mut buf).expect("padded input");
+        buf
+    }
+}
+
+/// Recovers the secret an ECB oracle appends to its input.
+pub fn ecb_byte_at_a_time(oracle: &mut impl Oracle) -> Result<Vec<u8>, AttackError> {
+    let layout = discover_layout(oracle)?;
+    let bs = layout.block_size;
+
+    // Filler that brings the prefix up to a block boundary, and the first
+    // block after it.
+    let align = (bs - layout.prefix_len % bs) % bs;
+    let first = (layout.prefix_len + align) / bs;
+
+    let mut secret: Vec<u8> = Vec::with_capacity(layout.secret_len);
+    for offset in 0..layout.secret_len {
+        // Slide the secret so its next unknown byte ends a block.
+        let shift = bs - 1 - offset % bs;
+        let target_index = first + offset / bs;
+        let ciphertext = oracle.query(&vec![b'A'; align + shift]);
+        let target = &ciphertext[target_index * bs..(target_index + 1) * bs];
+
+        // The last `bs - 1` known bytes, then each of the 256 guesses for
+        // the next one: a block per guess, all in a single query.
+        let known = [vec![b'A'; shift], secret.clone()].concat();
+        let window = &known[known.len() - (bs - 1)..];
+        let mut input = vec![b'A'; align];
+        for guess in 0..=255 {
+            input.extend_from_slice(window);
+            input.push(guess);
+        }
+        let dictionary = oracle.query(&input);
+
+        let guess = (0..=255u8)
+            .find(|&g| {
+                let i = first + g as usize;
+                &dictionary[i * bs..(i + 1) * bs] == target
+            })
+            .ok_or(AttackError::ByteNotFound { offset })?;
+        secret.push(guess);
+    }
+    Ok(secret)
+}
+
+/// Block size, ECB check, prefix length and secret length, in that order.
+pub fn discover_layout(oracle: &mut impl Oracle) -> Result<EcbLayout, AttackError> {
+    let block_size = discover_block_size(oracle)?;
+    if detect_ecb(&oracle.query(&vec![0; 3 * block_size]), block_size).repeated_blocks == 0 {
+        return Err(AttackError::NotEcb);
+    }
+    let prefix_len = find_prefix_len(oracle, block_size)?;
+
+    // The ciphertext grows once prefix, filler, secret and one more byte
+    // fill whole blocks, since PKCS#7 then adds a block of its own.
+    let base = oracle.query(&[]).len();
+    let filler = (1..=block_size)
+        .find(|&n| oracle.query(&vec![0; n]).len() > base)
+        .ok_or(AttackError::NoBlockSize)?;
+    let secret_len = base
+        .checked_sub(prefix_len + filler)
+        .ok_or(AttackError::LayoutMismatch)?;
+    Ok(EcbLayout {
+        block_size,
+        prefix_len,
+        secret_len,
+    })
+}
+
+/// Grows the input until the ciphertext grows; the step is one block.
+pub fn discover_block_size(oracle: &mut impl Oracle) -> Result<usize, AttackError> {
+    let base = oracle.query(&[]).len();
+    (1..=MAX_BLOCK_SIZE)
+        .map(|n| oracle.query(&vec![0; n]).len())
+        .find(|&len| len > base)
+        .map(|len| len - base)
+        .ok_or(AttackError::NoBlockSize)
+}
+
+/// Finds the shortest filler after which two whole blocks of chosen bytes
+/// line up; checking with two different fill bytes rules out repeats that
+/// were already in the prefix.
+pub fn find_prefix_len(oracle: &mut impl Oracle, block_size: usize) -> Result<usize, AttackError> {
+    let bs = block_size;
+    for pad in 0..bs {
+        let queries = [0u8, 1].map(|fill| {
+            let mut input = vec![0xff; pad];
+            input.extend(std::iter::repeat_n(fill, 2 * bs));
+            oracle.query(&input)
+        });
+        let blocks = queries
+            .each_ref()
+            .map(|c| c.chunks_exact(bs).collect::<Vec<_>>());
+        let found = (0..blocks[0].len().min(blocks[1].len()).saturating_sub(1))
+            .find(|&i| blocks.iter().all(|b| b[i] == b[i + 1]) && blocks[0][i] != blocks[1][i]);
+        if let Some(i) = found {
+            return Ok(i * bs - pad);
+        }
+    }
+    Err(AttackError::PrefixUnstable)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{attack::ecb_byte_at_a_time::*, encoding::base64_decode};
+
+    const C12_SECRET: &str = "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkg\
+aGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0\
+b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK";
+
+    #[test]
+    fn test_c12() {
+        let secret = base64_decode(C12_SECRET).unwrap();
+        let mut oracle = EcbSecretOracle::new(&secret);
+
+        assert_eq!(
+            discover_layout(&mut oracle),
+            Ok(EcbLayout {
+                block_size: 16,
+                prefix_len: 0,
+                secret_len: secret.len()
+            })
+        );
+        assert_eq!(ecb_byte_at_a_time(&mut oracle), Ok(secret));
+    }
+
+    #[test]
+    fn test_c14() {
+        let secret = base64_decode(C12_SECRET).unwrap();
+        for prefix_len in [1, 16, 40] {
+            // A prefix that repeats a block must not fool the alignment.
+            let prefix = vec![0u8; prefix_len];
+            let mut oracle = EcbSecretOracle::with_prefix(&prefix, &secret);
+            assert_eq!(
+                discover_layout(&mut oracle).map(|l| l.prefix_len),
+                Ok(prefix_len)
+            );
+            assert_eq!(ecb_byte_at_a_time(&mut oracle), Ok(secret.clone()));
+        }
+
+        let mut oracle = EcbSecretOracle::with_random_prefix(&secret);
+        assert_eq!(ecb_byte_at_a_time(&mut oracle), Ok(secret));
+    }
+
+    #[test]
+    fn test_rejects_non_ecb() {
+        // Padded, but each byte masked by its position, so no block repeats.
+        let mut oracle = |input: &[u8]| {
+            let mut out = pkcs7_pad(input, 16);
+            out.iter_mut()
+                .enumerate()
+                .for_each(|(i, b)| *b ^= (i * 37) as u8);
+            out
+        };
+        assert_eq!(discover_layout(&mut oracle), Err(AttackError::NotEcb));
+    }
+
+    #[test]
+    fn test_rejects_unpadded_ecb() {
+        // Zero-filled rather than padded: empty input gives no ciphertext,
+        // which leaves no room for a secret.
+        let ecb = Ecb::new(Aes128::new(b"YELLOW SUBMARINE"));
+        let mut oracle = |input: &[u8]| {
+            let mut out = input.to_vec();
+            out.resize(input.len().div_ceil(16) * 16, 0);
+            ecb.encrypt(&mut out).unwrap();
+            out
+        };
+        assert_eq!(
+            discover_layout(&mut oracle),
+            Err(AttackError::LayoutMismatch)
+        );
+    }
+}
@@ -0,0 +1,281 @@
+pub mod net;
+
+use std::{error::Error, fmt, io};
+
+use crate::{
+    aes::{self, Aes128, Block},
+    encoding::base64_decode,
+    modes::Cbc,
+    padding::{PaddingError, pkcs7_pad, pkcs7_unpad_block},
+    random::{random_array, random_in},
+};
+
+/// The ten challenge 17 strings, base64-encoded.
+const C17_STRINGS: [&str; 10] = [
+    "MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=",
+    "MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=",
+    "MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==",
+    "MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==",
+    "MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl",
+    "MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbA==",
+    "MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==",
+    "MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=",
+    "MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=",
+    "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93",
+];
+
+/// Answers only whether a CBC ciphertext decrypts to valid PKCS#7 padding.
+pub trait PaddingOracle {
+    fn padding_valid(&mut self, iv: &Block, ciphertext: &[u8]) -> io::Result<bool>;
+}
+
+impl<F: FnMut(&Block, &[u8]) -> io::Result<bool>> PaddingOracle for F {
+    fn padding_valid(&mut self, iv: &Block, ciphertext: &[u8]) -> io::Result<bool> {
+        self(iv, ciphertext)
+    }
+}
+
+/// Challenge 17: holds a secret key and ten strings, hands out one of the
+/// strings encrypted under a fresh IV, and checks padding on request.
+#[derive(Debug, Clone)]
+pub struct PaddingOracleServer {
+    cipher: Aes128,
+    strings: Vec<Vec<u8>>,
+    queries: u64,
+}
+
+impl PaddingOracleServer {
+    pub fn new() -> Self {
+        let strings = C17_STRINGS
+            .iter()
+            .map(|s| base64_decode(s).expect("valid base64"))
+            .collect();
+        PaddingOracleServer::with_strings(strings)
+    }
+
+    pub fn with_strings(strings: Vec<Vec<u8>>) -> Self {
+        assert!(!strings.is_empty(), "no strings to serve");
+        PaddingOracleServer {
+            cipher: Aes128::new(&random_array()),
+            strings,
+            queries: 0,
+        }
+    }
+
+    /// One of the strings at random, padded and encrypted, with its IV.
+    pub fn encrypt_random(&self) -> (Block, Vec<u8>) {
+        let plaintext = &self.strings[random_in(0..=self.strings.len() - 1)];
+        let iv = random_array();
+        let mut buf = pkcs7_pad(plaintext, aes::BLOCK_LEN);
+        self.cbc(&iv).encrypt(&mut buf).expect("padded input");
+        (iv, buf)
+    }
+
+    pub fn check_padding(&mut self, iv: &Block, ciphertext: &[u8]) -> bool {
+        self.queries += 1;
+        let mut buf = ciphertext.to_vec();
+        self.cbc(iv).decrypt(&mut buf).is_ok() && pkcs7_unpad_block(&buf, aes::BLOCK_LEN).is_ok()
+    }
+
+    /// Padding checks answered so far.
+    pub fn queries(&self) -> u64 {
+        self.queries
+    }
+
+    pub fn strings(&self) -> &[Vec<u8>] {
+        &self.strings
+    }
+
+    fn cbc(&self, iv: &Block) -> Cbc<Aes128> {
+        Cbc::new(self.cipher.clone(), iv).expect("block-sized IV")
+    }
+}
+
+impl Default for PaddingOracleServer {
+    fn default() -> Self {
+        PaddingOracleServer::new()
+    }
+}
+
+impl PaddingOracle for PaddingOracleServer {
+    fn padding_valid(&mut self, iv: &Block, ciphertext: &[u8]) -> io::Result<bool> {
+        Ok(self.check_padding(iv, ciphertext))
+    }
+}
+
+#[derive(Debug)]
+pub enum PaddingOracleError {
+    /// The ciphertext is empty or not whole blocks.
+    Unaligned(usize),
+    /// No value of this byte gave valid padding; the oracle is lying or
+    /// the ciphertext changed underneath us.
+    NoValidByte { block: usize, offset: usize },
+    /// The recovered plaintext is not itself validly padded.
+    Padding(PaddingError),
+    /// The oracle could not be reached.
+    Io(io::Error),
+}
+
+impl fmt::Display for PaddingOracleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaddingOracleError::Unaligned(len) => {
+                write!(f, "ciphertext of {len} bytes is not whole blocks")
+            }
+            PaddingOracleError::NoValidByte { block, offset } => {
+                write!(
+                    f,
+                    "no byte gave valid padding at block {block}, offset {offset}"
+                )
+            }
+            PaddingOracleError::Padding(e) => write!(f, "recovered plaintext: {e}"),
+            PaddingOracleError::Io(e) => write!(f, "oracle unreachable: {e}"),
+        }
+    }
+}
+
+impl Error for PaddingOracleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PaddingOracleError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PaddingOracleError {
+    fn from(e: io::Error) -> Self {
+        PaddingOracleError::Io(e)
+    }
+}
+
+/// Recovers the unpadded plaintext of `ciphertext`, one block at a time.
+pub fn padding_oracle_attack(
+    oracle: &mut impl PaddingOracle,
+    iv: &Block,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, PaddingOracleError> {
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(aes::BLOCK_LEN) {
+        return Err(PaddingOracleError::Unaligned(ciphertext.len()));
+    }
+
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+    let mut prev = *iv;
+    for (index, block) in ciphertext.chunks_exact(aes::BLOCK_LEN).enumerate() {
+        let intermediate = decrypt_intermediate(oracle, index, &prev, block)?;
+        plaintext.extend(intermediate.iter().zip(&prev).map(|(i, p)| i ^ p));
+        prev = block.try_into().expect("block-sized");
+    }
+
+    let len = pkcs7_unpad_block(&plaintext, aes::BLOCK_LEN)
+        .map_err(PaddingOracleError::Padding)?
+        .len();
+    plaintext.truncate(len);
+    Ok(plaintext)
+}
+
+/// The block cipher's raw output for `block`, before CBC's XOR, found by
+/// forging the block before it one byte at a time from the end.
+fn decrypt_intermediate(
+    oracle: &mut impl PaddingOracle,
+    index: usize,
+    prev: &Block,
+    block: &[u8],
+) -> Result<Block, PaddingOracleError> {
+    let mut intermediate = [0u8; aes::BLOCK_LEN];
+    for offset in (0..aes::BLOCK_LEN).rev() {
+        let pad = (aes::BLOCK_LEN - offset) as u8;
+        // Starting from the real previous block keeps the bytes ahead of
+        // `offset` as they decrypt in the genuine message.
+        let mut forged = *prev;
+        for i in offset + 1..aes::BLOCK_LEN {
+            forged[i] = intermediate[i] ^ pad;
+        }
+
+        let mut found = None;
+        for guess in 0..=255u8 {
+            forged[offset] = guess;
+            if !oracle.padding_valid(&forged, block)? {
+                continue;
+            }
+            // On the last byte, valid padding may be `02 02` or longer
+            // rather than `01`; disturbing the byte before tells them apart.
+            if offset == aes::BLOCK_LEN - 1 {
+                let mut probe = forged;
+                probe[offset - 1] ^= 0xff;
+                if !oracle.padding_valid(&probe, block)? {
+                    continue;
+                }
+            }
+            found = Some(guess);
+            break;
+        }
+
+        let guess = found.ok_or(PaddingOracleError::NoValidByte {
+            block: index,
+            offset,
+        })?;
+        intermediate[offset] = guess ^ pad;
+    }
+    Ok(intermediate)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attack::padding_oracle::*;
+
+    #[test]
+    fn test_c17() {
+        let mut server = PaddingOracleServer::new();
+        for (i, s) in server.strings().iter().enumerate() {
+            assert!(s.starts_with(format!("00000{i}").as_bytes()));
+        }
+
+        for _ in 0..5 {
+            let (iv, ciphertext) = server.encrypt_random();
+            let plaintext = padding_oracle_attack(&mut server, &iv, &ciphertext).unwrap();
+            assert!(server.strings().contains(&plaintext));
+        }
+    }
+
+    #[test]
+    fn test_last_byte_ambiguity() {
+        // A block ending `02 ??` gives a second valid guess for the last
+        // byte, `02 02`, which must be rejected.
+        let plaintext = b"fourteen bytes\x02".to_vec();
+        let mut server = PaddingOracleServer::with_strings(vec![plaintext.clone()]);
+        for _ in 0..20 {
+            let (iv, ciphertext) = server.encrypt_random();
+            assert_eq!(
+                padding_oracle_attack(&mut server, &iv, &ciphertext).unwrap(),
+                plaintext
+            );
+        }
+
+        let mut server = PaddingOracleServer::with_strings(vec![vec![b'x'; 32]]);
+        let (iv, ciphertext) = server.encrypt_random();
+        assert_eq!(
+            padding_oracle_attack(&mut server, &iv, &ciphertext).unwrap(),
+            [b'x'; 32]
+        );
+        assert!(server.queries() > 0);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut server = PaddingOracleServer::new();
+        assert!(matches!(
+            padding_oracle_attack(&mut server, &[0; 16], &[0; 15]),
+            Err(PaddingOracleError::Unaligned(15))
+        ));
+
+        let mut liar = |_: &Block, _: &[u8]| Ok(false);
+        assert!(matches!(
+            padding_oracle_attack(&mut liar, &[0; 16], &[0; 16]),
+            Err(PaddingOracleError::NoValidByte {
+                block: 0,
+                offset: 15
+            })
+        ));
+    }
+}
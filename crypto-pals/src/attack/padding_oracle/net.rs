@@ -0,0 +1,372 @@
+//! The padding oracle over a loopback TCP socket.
+//!
+//! Each request is one opcode byte. `E` asks for a challenge and gets back
+//! the IV, a big-endian `u32` length and the ciphertext. `P` sends a
+//! big-endian `u64` request id, then an IV, length and ciphertext in the
+//! same layout, and gets back one byte, 1 if the padding is valid and 0 if
+//! not. A resent `P` with the id just answered gets the same answer without
+//! a second check, so each query is counted once even when a reply is lost.
+
+use std::{
+    io::{self, BufReader, BufWriter, Read, Write},
+    net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    aes::{self, Block},
+    attack::padding_oracle::{PaddingOracle, PaddingOracleServer},
+    random::random_array,
+};
+
+const OP_ENCRYPT: u8 = b'E';
+const OP_PADDING: u8 = b'P';
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest ciphertext either side will accept.
+const MAX_CIPHERTEXT: usize = 1 << 16;
+
+fn write_message(w: &mut impl Write, iv: &Block, ciphertext: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(ciphertext.len())
+        .ok()
+        .filter(|&len| len as usize <= MAX_CIPHERTEXT)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "ciphertext too long"))?;
+    w.write_all(iv)?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(ciphertext)
+}
+
+fn read_message(r: &mut impl Read) -> io::Result<(Block, Vec<u8>)> {
+    let mut iv = [0u8; aes::BLOCK_LEN];
+    r.read_exact(&mut iv)?;
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_CIPHERTEXT {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "ciphertext too long",
+        ));
+    }
+    let mut ciphertext = vec![0u8; len];
+    r.read_exact(&mut ciphertext)?;
+    Ok((iv, ciphertext))
+}
+
+/// Serves a [`PaddingOracleServer`] on `127.0.0.1` from a background
+/// thread, one connection at a time.
+///
+/// Dropping it without [`shutdown`](Self::shutdown) stops it too, without
+/// waiting for the thread.
+pub struct LoopbackServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<PaddingOracleServer>>,
+}
+
+impl LoopbackServer {
+    pub fn spawn(server: PaddingOracleServer) -> io::Result<Self> {
+        LoopbackServer::spawn_with(server, None)
+    }
+
+    /// Like [`spawn`](Self::spawn), but hangs up on each client after
+    /// `requests_per_connection` requests, to exercise reconnecting.
+    pub fn spawn_with(
+        mut server: PaddingOracleServer,
+        requests_per_connection: Option<usize>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stopping = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let mut last_answer = None;
+            for stream in listener.incoming() {
+                if stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                // A client that misbehaves only loses its own connection.
+                if let Ok(stream) = stream {
+                    let _ = serve(
+                        &mut server,
+                        &mut last_answer,
+                        stream,
+                        requests_per_connection,
+                    );
+                }
+            }
+            server
+        });
+
+        Ok(LoopbackServer {
+            addr,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops accepting and hands back the server, with its query count.
+    /// Clients should be dropped first; an open connection is only closed
+    /// by its read timeout.
+    pub fn shutdown(mut self) -> PaddingOracleServer {
+        self.stop();
+        let handle = self.handle.take().expect("not yet shut down");
+        handle.join().expect("server thread panicked")
+    }
+
+    fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+impl Drop for LoopbackServer {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.stop();
+        }
+    }
+}
+
+/// `last_answer` is the id and answer of the latest padding check, kept
+/// across connections so a client's resend is not checked twice.
+fn serve(
+    server: &mut PaddingOracleServer,
+    last_answer: &mut Option<(u64, bool)>,
+    stream: TcpStream,
+    limit: Option<usize>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+
+    let mut served = 0;
+    while limit.is_none_or(|limit| served < limit) {
+        let mut op = [0u8];
+        if reader.read(&mut op)? == 0 {
+            return Ok(());
+        }
+        match op[0] {
+            OP_ENCRYPT => {
+                let (iv, ciphertext) = server.encrypt_random();
+                write_message(&mut writer, &iv, &ciphertext)?;
+            }
+            OP_PADDING => {
+                let mut id = [0u8; 8];
+                reader.read_exact(&mut id)?;
+                let id = u64::from_be_bytes(id);
+                let (iv, ciphertext) = read_message(&mut reader)?;
+                let valid = match *last_answer {
+                    Some((last, valid)) if last == id => valid,
+                    _ => server.check_padding(&iv, &ciphertext),
+                };
+                *last_answer = Some((id, valid));
+                writer.write_all(&[valid as u8])?;
+            }
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown opcode"));
+            }
+        }
+        writer.flush()?;
+        served += 1;
+    }
+    writer.get_ref().shutdown(Shutdown::Both)
+}
+
+/// Talks to a [`LoopbackServer`], reconnecting and resending when a
+/// request fails.
+#[derive(Debug)]
+pub struct PaddingOracleClient {
+    addr: SocketAddr,
+    stream: Option<(BufReader<TcpStream>, BufWriter<TcpStream>)>,
+    max_retries: u32,
+    next_id: u64,
+    queries: u64,
+    retries: u64,
+}
+
+impl PaddingOracleClient {
+    /// Connects lazily, on the first request. Only loopback addresses are
+    /// accepted.
+    pub fn new(addr: SocketAddr) -> io::Result<Self> {
+        if !addr.ip().is_loopback() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "padding oracle client only connects to loopback",
+            ));
+        }
+        Ok(PaddingOracleClient {
+            addr,
+            stream: None,
+            max_retries: 3,
+            // Random so that two clients of one server do not collide.
+            next_id: u64::from_le_bytes(random_array()),
+            queries: 0,
+            retries: 0,
+        })
+    }
+
+    /// Attempts after the first before a request gives up.
+    pub fn with_max_retries(self, max_retries: u32) -> Self {
+        PaddingOracleClient {
+            max_retries,
+            ..self
+        }
+    }
+
+    pub fn fetch_challenge(&mut self) -> io::Result<(Block, Vec<u8>)> {
+        self.request(|reader, writer| {
+            writer.write_all(&[OP_ENCRYPT])?;
+            writer.flush()?;
+            read_message(reader)
+        })
+    }
+
+    /// Padding checks answered so far, not counting failed attempts.
+    pub fn queries(&self) -> u64 {
+        self.queries
+    }
+
+    /// Requests that had to be resent.
+    pub fn retries(&self) -> u64 {
+        self.retries
+    }
+
+    fn request<T>(
+        &mut self,
+        mut send: impl FnMut(&mut BufReader<TcpStream>, &mut BufWriter<TcpStream>) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut attempt = 0;
+        loop {
+            let result = self.connection().and_then(|(r, w)| send(r, w));
+            match result {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= self.max_retries => {
+                    self.stream = None;
+                    return Err(e);
+                }
+                Err(_) => {
+                    self.stream = None;
+                    self.retries += 1;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    fn connection(&mut self) -> io::Result<&mut (BufReader<TcpStream>, BufWriter<TcpStream>)> {
+        if self.stream.is_none() {
+            let stream = TcpStream::connect_timeout(&self.addr, TIMEOUT)?;
+            stream.set_read_timeout(Some(TIMEOUT))?;
+            stream.set_write_timeout(Some(TIMEOUT))?;
+            stream.set_nodelay(true)?;
+            self.stream = Some((BufReader::new(stream.try_clone()?), BufWriter::new(stream)));
+        }
+        Ok(self.stream.as_mut().expect("just connected"))
+    }
+}
+
+impl PaddingOracle for PaddingOracleClient {
+    fn padding_valid(&mut self, iv: &Block, ciphertext: &[u8]) -> io::Result<bool> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let valid = self.request(|reader, writer| {
+            writer.write_all(&[OP_PADDING])?;
+            writer.write_all(&id.to_be_bytes())?;
+            write_message(writer, iv, ciphertext)?;
+            writer.flush()?;
+            let mut reply = [0u8];
+            reader.read_exact(&mut reply)?;
+            match reply[0] {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(io::Error::new(io::ErrorKind::InvalidData, "bad reply")),
+            }
+        })?;
+        self.queries += 1;
+        Ok(valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attack::padding_oracle::{net::*, padding_oracle_attack};
+
+    #[test]
+    fn test_c17_over_loopback() {
+        let strings = PaddingOracleServer::new().strings().to_vec();
+        let server = LoopbackServer::spawn_with(PaddingOracleServer::new(), Some(500)).unwrap();
+
+        let mut client = PaddingOracleClient::new(server.local_addr()).unwrap();
+        let (iv, ciphertext) = client.fetch_challenge().unwrap();
+        let plaintext = padding_oracle_attack(&mut client, &iv, &ciphertext).unwrap();
+        assert!(strings.contains(&plaintext));
+
+        // The hang-ups forced reconnects, and every query was checked
+        // exactly once.
+        let (queries, retries) = (client.queries(), client.retries());
+        drop(client);
+        assert!(retries > 0);
+        assert_eq!(server.shutdown().queries(), queries);
+    }
+
+    #[test]
+    fn test_resent_query_checked_once() {
+        let server = LoopbackServer::spawn(PaddingOracleServer::new()).unwrap();
+        let mut request = vec![OP_PADDING];
+        request.extend_from_slice(&7u64.to_be_bytes());
+        write_message(&mut request, &[0; 16], &[0; 16]).unwrap();
+
+        // The same id twice, as a client would send it after losing the
+        // first reply, then a fresh id.
+        let mut replies = Vec::new();
+        for id in [7u64, 7, 8] {
+            request[1..9].copy_from_slice(&id.to_be_bytes());
+            let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+            stream.write_all(&request).unwrap();
+            let mut reply = [0u8];
+            stream.read_exact(&mut reply).unwrap();
+            replies.push(reply[0]);
+        }
+        assert_eq!(replies[0], replies[1]);
+        assert_eq!(server.shutdown().queries(), 2);
+    }
+
+    #[test]
+    fn test_drop_stops_server() {
+        let server = LoopbackServer::spawn(PaddingOracleServer::new()).unwrap();
+        let addr = server.local_addr();
+        drop(server);
+
+        // Give the thread a moment to leave `accept` and close the socket.
+        let deadline = std::time::Instant::now() + TIMEOUT;
+        while TcpStream::connect(addr).is_ok() {
+            assert!(std::time::Instant::now() < deadline, "still listening");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_client_gives_up() {
+        let server = LoopbackServer::spawn(PaddingOracleServer::new()).unwrap();
+        let addr = server.local_addr();
+        server.shutdown();
+
+        let mut client = PaddingOracleClient::new(addr).unwrap().with_max_retries(2);
+        assert!(client.padding_valid(&[0; 16], &[0; 16]).is_err());
+        assert_eq!((client.queries(), client.retries()), (0, 2));
+
+        assert!(PaddingOracleClient::new("192.0.2.1:80".parse().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,390 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    aes::{self, Aes128},
+    modes::{Ecb, ModeError},
+    padding::{PaddingError, pkcs7_pad, pkcs7_unpad_block},
+    random::random_array,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CookieError {
+    /// A field with no `=`.
+    MissingEquals {
+        offset: usize,
+    },
+    /// A second unescaped `=` in one field.
+    UnexpectedEquals {
+        offset: usize,
+    },
+    /// An empty key, including empty fields such as `a=1&&b=2`.
+    EmptyKey {
+        offset: usize,
+    },
+    /// `%` not followed by two hex digits.
+    BadEscape {
+        offset: usize,
+    },
+    DuplicateKey(String),
+    /// The unescaped text is not UTF-8.
+    InvalidUtf8,
+}
+
+impl fmt::Display for CookieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CookieError::MissingEquals { offset } => {
+                write!(f, "field at offset {offset} has no '='")
+            }
+            CookieError::UnexpectedEquals { offset } => {
+                write!(f, "unexpected '=' at offset {offset}")
+            }
+            CookieError::EmptyKey { offset } => write!(f, "empty key at offset {offset}"),
+            CookieError::BadEscape { offset } => write!(f, "bad escape at offset {offset}"),
+            CookieError::DuplicateKey(key) => write!(f, "duplicate key {key:?}"),
+            CookieError::InvalidUtf8 => write!(f, "cookie is not valid UTF-8"),
+        }
+    }
+}
+
+impl Error for CookieError {}
+
+/// The separators of a `k=v` cookie. Both, and `%`, are percent-escaped
+/// wherever they occur in a key or value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CookieFormat {
+    pub pair_sep: u8,
+    pub kv_sep: u8,
+}
+
+impl CookieFormat {
+    /// `k=v&k=v`.
+    pub const QUERY: CookieFormat = CookieFormat {
+        pair_sep: b'&',
+        kv_sep: b'=',
+    };
+
+    /// `k=v;k=v`.
+    pub const SEMICOLON: CookieFormat = CookieFormat {
+        pair_sep: b';',
+        kv_sep: b'=',
+    };
+
+    /// Percent-escapes the separators and `%` in `s`.
+    pub fn quote(self, s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        self.escape(s, &mut out);
+        out
+    }
+
+    fn escape(self, s: &str, out: &mut String) {
+        for c in s.chars() {
+            if c == '%' || c == self.pair_sep as char || c == self.kv_sep as char {
+                out.push_str(&format!("%{:02X}", c as u8));
+            } else {
+                out.push(c);
+            }
+        }
+    }
+}
+
+impl Default for CookieFormat {
+    fn default() -> Self {
+        CookieFormat::QUERY
+    }
+}
+
+/// Ordered, unique `k=v` pairs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cookie {
+    pairs: Vec<(String, String)>,
+}
+
+impl Cookie {
+    pub fn new() -> Self {
+        Cookie::default()
+    }
+
+    pub fn parse(s: &str) -> Result<Self, CookieError> {
+        Cookie::parse_with(s.as_bytes(), CookieFormat::QUERY)
+    }
+
+    /// Parses raw bytes, such as a decryption, so stray bytes are reported
+    /// rather than lost.
+    pub fn parse_with(input: &[u8], format: CookieFormat) -> Result<Self, CookieError> {
+        let mut cookie = Cookie::new();
+        if input.is_empty() {
+            return Ok(cookie);
+        }
+        let mut offset = 0;
+        for field in input.split(|&b| b == format.pair_sep) {
+            let mut parts = field.splitn(2, |&b| b == format.kv_sep);
+            let key = parts.next().unwrap_or_default();
+            if key.is_empty() {
+                return Err(CookieError::EmptyKey { offset });
+            }
+            let value = parts.next().ok_or(CookieError::MissingEquals { offset })?;
+            let value_offset = offset + key.len() + 1;
+            if let Some(i) = value.iter().position(|&b| b == format.kv_sep) {
+                return Err(CookieError::UnexpectedEquals {
+                    offset: value_offset + i,
+                });
+            }
+
+            let key = unescape(key, offset)?;
+            let value = unescape(value, value_offset)?;
+            if cookie.get(&key).is_some() {
+                return Err(CookieError::DuplicateKey(key));
+            }
+            cookie.pairs.push((key, value));
+            offset += field.len() + 1;
+        }
+        Ok(cookie)
+    }
+
+    pub fn encode(&self) -> String {
+        self.encode_with(CookieFormat::QUERY)
+    }
+
+    pub fn encode_with(&self, format: CookieFormat) -> String {
+        let mut out = String::new();
+        for (i, (key, value)) in self.pairs.iter().enumerate() {
+            if i > 0 {
+                out.push(format.pair_sep as char);
+            }
+            format.escape(key, &mut out);
+            out.push(format.kv_sep as char);
+            format.escape(value, &mut out);
+        }
+        out
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Sets `key`, replacing any earlier value in place.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.pairs.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.pairs.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+fn unescape(bytes: &[u8], offset: usize) -> Result<String, CookieError> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or(CookieError::BadEscape { offset: offset + i })?;
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| CookieError::InvalidUtf8)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileError {
+    Mode(ModeError),
+    Padding(PaddingError),
+    Cookie(CookieError),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Mode(e) => write!(f, "bad ciphertext: {e}"),
+            ProfileError::Padding(e) => write!(f, "bad padding: {e}"),
+            ProfileError::Cookie(e) => write!(f, "bad profile: {e}"),
+        }
+    }
+}
+
+impl Error for ProfileError {}
+
+impl From<ModeError> for ProfileError {
+    fn from(e: ModeError) -> Self {
+        ProfileError::Mode(e)
+    }
+}
+
+impl From<PaddingError> for ProfileError {
+    fn from(e: PaddingError) -> Self {
+        ProfileError::Padding(e)
+    }
+}
+
+impl From<CookieError> for ProfileError {
+    fn from(e: CookieError) -> Self {
+        ProfileError::Cookie(e)
+    }
+}
+
+/// Challenge 13: hands out encrypted `email=..&uid=10&role=user` profiles
+/// under a key of its own, and reads them back.
+#[derive(Debug, Clone)]
+pub struct ProfileService {
+    ecb: Ecb<Aes128>,
+}
+
+impl ProfileService {
+    pub fn new() -> Self {
+        ProfileService {
+            ecb: Ecb::new(Aes128::new(&random_array())),
+        }
+    }
+
+    pub fn profile_for(&self, email: &str) -> Vec<u8> {
+        let mut profile = Cookie::new();
+        profile.set("email", email);
+        profile.set("uid", "10");
+        profile.set("role", "user");
+
+        let mut buf = pkcs7_pad(profile.encode().as_bytes(), aes::BLOCK_LEN);
+        self.ecb.encrypt(&mut buf).expect("padded input");
+        buf
+    }
+
+    pub fn decrypt_profile(&self, ciphertext: &[u8]) -> Result<Cookie, ProfileError> {
+        let mut buf = ciphertext.to_vec();
+        self.ecb.decrypt(&mut buf)?;
+        Ok(Cookie::parse_with(
+            pkcs7_unpad_block(&buf, aes::BLOCK_LEN)?,
+            CookieFormat::QUERY,
+        )?)
+    }
+
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        self.decrypt_profile(ciphertext)
+            .is_ok_and(|profile| profile.get("role") == Some("admin"))
+    }
+}
+
+impl Default for ProfileService {
+    fn default() -> Self {
+        ProfileService::new()
+    }
+}
+
+/// Builds an admin profile from `profile_for` alone, assuming only that the
+/// encoding starts `email=` and ends with the role. One email puts a padded
+/// `admin` block on a block boundary; another pushes `user` into a last
+/// block of its own, which the `admin` block then replaces.
+pub fn forge_admin(profile_for: impl Fn(&str) -> Vec<u8>) -> Vec<u8> {
+    const BLOCK: usize = aes::BLOCK_LEN;
+    const PREFIX: usize = "email=".len();
+    const DOMAIN: &str = "@bar.com";
+
+    let mut email = "x".repeat(BLOCK - PREFIX);
+    email.push_str(std::str::from_utf8(&pkcs7_pad(b"admin", BLOCK)).expect("ASCII"));
+    let admin_block = profile_for(&email)[BLOCK..2 * BLOCK].to_vec();
+
+    // The ciphertext gains a block when the encoding fills whole blocks;
+    // four more bytes leave `user` alone in the last one.
+    let base = profile_for("").len();
+    let fill = (1..=BLOCK)
+        .find(|&n| profile_for(&"x".repeat(n)).len() > base)
+        .expect("ciphertext grows within a block");
+    let mut email_len = fill + 4;
+    while email_len <= DOMAIN.len() {
+        email_len += BLOCK;
+    }
+    let email = format!("{}{DOMAIN}", "f".repeat(email_len - DOMAIN.len()));
+
+    let mut forged = profile_for(&email);
+    forged.truncate(forged.len() - BLOCK);
+    forged.extend_from_slice(&admin_block);
+    forged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attack::profile::*;
+
+    #[test]
+    fn test_cookie_round_trip() {
+        let cookie = Cookie::parse("foo=bar&baz=qux&zap=zazzle").unwrap();
+        assert_eq!(cookie.get("baz"), Some("qux"));
+        assert_eq!(cookie.encode(), "foo=bar&baz=qux&zap=zazzle");
+
+        let mut cookie = Cookie::new();
+        cookie.set("email", "foo@bar.com&role=admin");
+        cookie.set("note", "100%");
+        let encoded = cookie.encode();
+        assert_eq!(encoded, "email=foo@bar.com%26role%3Dadmin&note=100%25");
+        assert_eq!(Cookie::parse(&encoded), Ok(cookie.clone()));
+
+        let encoded = cookie.encode_with(CookieFormat::SEMICOLON);
+        assert_eq!(encoded, "email=foo@bar.com&role%3Dadmin;note=100%25");
+        assert_eq!(
+            Cookie::parse_with(encoded.as_bytes(), CookieFormat::SEMICOLON),
+            Ok(cookie)
+        );
+    }
+
+    #[test]
+    fn test_cookie_errors() {
+        for (input, err) in [
+            ("a=1&b", CookieError::MissingEquals { offset: 4 }),
+            ("a=1&&b=2", CookieError::EmptyKey { offset: 4 }),
+            ("=1", CookieError::EmptyKey { offset: 0 }),
+            ("a=1=2", CookieError::UnexpectedEquals { offset: 3 }),
+            ("a=1&b=%2", CookieError::BadEscape { offset: 6 }),
+            ("a=%zz", CookieError::BadEscape { offset: 2 }),
+            ("a=1&a=2", CookieError::DuplicateKey("a".to_string())),
+            ("a=%ff", CookieError::InvalidUtf8),
+        ] {
+            assert_eq!(Cookie::parse(input), Err(err), "{input}");
+        }
+        assert_eq!(Cookie::parse("a=").unwrap().get("a"), Some(""));
+        assert_eq!(Cookie::parse(""), Ok(Cookie::new()));
+    }
+
+    #[test]
+    fn test_profile_for() {
+        let service = ProfileService::new();
+        let profile = service
+            .decrypt_profile(&service.profile_for("foo@bar.com&role=admin"))
+            .unwrap();
+        assert_eq!(
+            profile.pairs().collect::<Vec<_>>(),
+            [
+                ("email", "foo@bar.com&role=admin"),
+                ("uid", "10"),
+                ("role", "user")
+            ]
+        );
+        assert!(!service.is_admin(&service.profile_for("foo@bar.com")));
+        assert!(matches!(
+            service.decrypt_profile(&[0; 15]),
+            Err(ProfileError::Mode(_))
+        ));
+    }
+
+    #[test]
+    fn test_c13() {
+        let service = ProfileService::new();
+        let forged = forge_admin(|email| service.profile_for(email));
+
+        assert!(service.is_admin(&forged));
+        let profile = service.decrypt_profile(&forged).unwrap();
+        assert_eq!(profile.get("uid"), Some("10"));
+        assert!(profile.get("email").unwrap().ends_with("@bar.com"));
+    }
+}
@@ -0,0 +1,47 @@
+pub fn transpose_blocks(raw: &[u8], key_size: usize) -> Vec<Vec<u8>> {
+    let blocks = raw.chunks(key_size);
+    let mut transpose = vec![Vec::new(); key_size];
+
+    for block in blocks {
+        for (i, &byte) in block.iter().enumerate() {
+            transpose[i].push(byte);
+        }
+    }
+    transpose
+}
+
+pub fn reassemble_text(decrypted_columns: &[Vec<u8>]) -> Vec<u8> {
+    let mut final_plaintext = Vec::new();
+    let max_len = decrypted_columns
+        .iter()
+        .map(|col| col.len())
+        .max()
+        .unwrap_or(0);
+
+    for row_index in 0..max_len {
+        for column in decrypted_columns {
+            if let Some(&byte) = column.get(row_index) {
+                final_plaintext.push(byte);
+            }
+        }
+    }
+
+    final_plaintext
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::block::*;
+
+    #[test]
+    fn test_transpose_round_trip() {
+        let raw = b"abcdefghij";
+        let transposed = transpose_blocks(raw, 3);
+
+        assert_eq!(
+            transposed,
+            vec![b"adgj".to_vec(), b"beh".to_vec(), b"cfi".to_vec()]
+        );
+        assert_eq!(reassemble_text(&transposed), raw);
+    }
+}
@@ -0,0 +1,170 @@
+pub mod ascii85;
+pub mod base32;
+pub mod base58;
+pub mod base64;
+pub mod detect;
+pub mod hex;
+pub mod stream;
+
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+};
+
+pub use base64::Alphabet;
+pub use detect::{Ciphertext, Encoding, detect_encoding, load_ciphertext};
+pub use hex::{Case, HexError, HexOptions};
+pub use stream::{
+    Ascii85Reader, Ascii85Writer, Base32Reader, Base32Writer, Base58Reader, Base58Writer,
+    Base64Reader, Base64Writer, HexReader, HexWriter,
+};
+
+/// Decoding error shared by the base32, base58, base64 and ascii85 codecs.
+/// Offsets are byte offsets into the encoded input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A character outside the alphabet.
+    InvalidChar { c: char, offset: usize },
+    /// The number of symbols (padding included) cannot form a whole encoding.
+    InvalidLength(usize),
+    /// A pad character where padding is not allowed, or data following padding.
+    InvalidPadding { offset: usize },
+    /// The last symbol before the padding carries non-zero unused bits.
+    TrailingBits { offset: usize },
+    /// A group starting at this offset decodes to a value too large for its width.
+    Overflow { offset: usize },
+}
+
+impl DecodeError {
+    /// Streaming decoders only see bytes; this is their best guess at the char.
+    pub(crate) fn invalid_byte(b: u8, offset: usize) -> Self {
+        let c = if b.is_ascii() { b as char } else { '\u{fffd}' };
+        DecodeError::InvalidChar { c, offset }
+    }
+
+    /// Reports the full character from `input` rather than its first UTF-8 byte.
+    pub(crate) fn with_input_char(self, input: &str) -> Self {
+        match self {
+            DecodeError::InvalidChar { offset, .. } => DecodeError::InvalidChar {
+                c: input_char(input, offset),
+                offset,
+            },
+            e => e,
+        }
+    }
+}
+
+/// The character of `input` starting at byte `offset`.
+pub(crate) fn input_char(input: &str, offset: usize) -> char {
+    input[offset..].chars().next().unwrap_or('\u{fffd}')
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidChar { c, offset } => {
+                write!(f, "invalid character {c:?} at offset {offset}")
+            }
+            DecodeError::InvalidLength(len) => write!(f, "invalid encoded length: {len}"),
+            DecodeError::InvalidPadding { offset } => {
+                write!(f, "invalid padding at offset {offset}")
+            }
+            DecodeError::TrailingBits { offset } => {
+                write!(f, "non-zero trailing bits at offset {offset}")
+            }
+            DecodeError::Overflow { offset } => {
+                write!(f, "group at offset {offset} overflows")
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+impl From<HexError> for DecodeError {
+    fn from(e: HexError) -> Self {
+        match e {
+            HexError::OddLength(digits) => DecodeError::InvalidLength(digits),
+            HexError::InvalidChar { c, offset } => DecodeError::InvalidChar { c, offset },
+        }
+    }
+}
+
+pub fn base64_encode(input: &[u8]) -> String {
+    base64::encode(input)
+}
+
+pub fn base64_decode(input: &str) -> Result<Vec<u8>, DecodeError> {
+    base64::decode(input)
+}
+
+pub fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, HexError> {
+    hex::decode(hex)
+}
+
+pub fn bytes_to_hex(bytes: &[u8]) -> String {
+    hex::encode(bytes)
+}
+
+pub fn hex_to_base64(hex_str: &str) -> Result<String, HexError> {
+    let bytes = hex_to_bytes(hex_str)?;
+    Ok(base64_encode(&bytes))
+}
+
+/// Opens a (possibly line-wrapped) base64 file as a decoding stream.
+pub fn open_b64(path: &str) -> io::Result<Base64Reader<BufReader<File>>> {
+    let f = File::open(path)?;
+    Ok(Base64Reader::new(BufReader::new(f), base64::Config::MIME))
+}
+
+/// Decodes a (possibly line-wrapped) base64 file.
+pub fn read_and_decode_b64(path: &str) -> io::Result<Vec<u8>> {
+    let mut raw = Vec::new();
+    open_b64(path)?.read_to_end(&mut raw)?;
+    Ok(raw)
+}
+
+/// Decodes one hex string per line, tolerating surrounding whitespace and
+/// `0x` prefixes.
+pub fn read_and_decode_hex(path: &str) -> io::Result<Vec<Vec<u8>>> {
+    let f = File::open(path)?;
+    let reader = BufReader::new(f);
+    let mut vec = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let dehexed = hex::decode_with(&line, HexOptions::tolerant())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        vec.push(dehexed);
+    }
+    Ok(vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        for input in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(base64_decode(&base64_encode(input)).unwrap(), input);
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg==");
+    }
+
+    #[test]
+    fn test_c1() {
+        let st = "49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d";
+
+        let base64 = hex_to_base64(st).unwrap();
+
+        assert_eq!(
+            base64,
+            "SSdtIGtpbGxpbmcgeW91ciBicmFpbiBsaWtlIGEgcG9pc29ub3VzIG11c2hyb29t"
+        );
+        assert_eq!(hex_to_base64("4"), Err(HexError::OddLength(1)));
+    }
+}
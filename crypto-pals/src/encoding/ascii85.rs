@@ -0,0 +1,285 @@
+use crate::encoding::{DecodeError, stream};
+
+const Z85_TABLE: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+const INVALID: u8 = 0xff;
+
+const Z85_DECODE: [u8; 256] = {
+    let mut map = [INVALID; 256];
+    let mut i = 0;
+    while i < Z85_TABLE.len() {
+        map[Z85_TABLE[i] as usize] = i as u8;
+        i += 1;
+    }
+    map
+};
+
+/// Symbols per 4-byte input group.
+const GROUP: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Variant {
+    /// Adobe/btoa Ascii85: digits `!`..=`u`, `z` for an all-zero group and
+    /// whitespace ignored on decode. No `<~ ~>` delimiters.
+    #[default]
+    Ascii85,
+    /// ZeroMQ Z85. The spec requires whole groups; here a trailing partial
+    /// group is carried the same way as Ascii85 does.
+    Z85,
+}
+
+impl Variant {
+    fn symbol(self, digit: u32) -> u8 {
+        match self {
+            Variant::Ascii85 => b'!' + digit as u8,
+            Variant::Z85 => Z85_TABLE[digit as usize],
+        }
+    }
+
+    fn digit(self, b: u8) -> Option<u32> {
+        match self {
+            Variant::Ascii85 => (b'!'..=b'u').contains(&b).then(|| (b - b'!') as u32),
+            Variant::Z85 => Some(Z85_DECODE[b as usize])
+                .filter(|&d| d != INVALID)
+                .map(u32::from),
+        }
+    }
+}
+
+pub fn encode(input: &[u8]) -> String {
+    encode_with(input, Variant::Ascii85)
+}
+
+pub fn encode_with(input: &[u8], variant: Variant) -> String {
+    use stream::Encode;
+
+    let mut output = Vec::with_capacity(input.len().div_ceil(4) * GROUP);
+    let mut encoder = Encoder::new(variant);
+    encoder.update(input, &mut output);
+    encoder.finish(&mut output);
+    String::from_utf8(output).expect("ascii85 output is ASCII")
+}
+
+pub fn decode(input: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_with(input, Variant::Ascii85)
+}
+
+pub fn decode_with(input: &str, variant: Variant) -> Result<Vec<u8>, DecodeError> {
+    use stream::Decode;
+
+    let mut output = Vec::with_capacity(input.len() / GROUP * 4);
+    let mut decoder = Decoder::new(variant);
+    decoder
+        .update(input.as_bytes(), &mut output)
+        .and_then(|_| decoder.finish(&mut output))
+        .map_err(|e| e.with_input_char(input))?;
+    Ok(output)
+}
+
+/// Incremental encoder behind [`Ascii85Writer`](stream::Ascii85Writer).
+pub struct Encoder {
+    variant: Variant,
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl Encoder {
+    pub fn new(variant: Variant) -> Self {
+        Encoder {
+            variant,
+            pending: [0; 4],
+            pending_len: 0,
+        }
+    }
+
+    /// A partial group of `n` bytes is zero-padded and truncated to `n + 1`
+    /// symbols.
+    fn encode_group(&self, group: &[u8], output: &mut Vec<u8>) {
+        let mut buffer = [0u8; 4];
+        buffer[..group.len()].copy_from_slice(group);
+        let mut n = u32::from_be_bytes(buffer);
+
+        if n == 0 && group.len() == 4 && self.variant == Variant::Ascii85 {
+            output.push(b'z');
+            return;
+        }
+
+        let mut symbols = [0u8; GROUP];
+        for symbol in symbols.iter_mut().rev() {
+            *symbol = self.variant.symbol(n % 85);
+            n /= 85;
+        }
+        output.extend_from_slice(&symbols[..group.len() + 1]);
+    }
+}
+
+impl stream::Encode for Encoder {
+    fn update(&mut self, mut input: &[u8], output: &mut Vec<u8>) {
+        if self.pending_len > 0 {
+            let take = (4 - self.pending_len).min(input.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&input[..take]);
+            self.pending_len += take;
+            input = &input[take..];
+            if self.pending_len < 4 {
+                return;
+            }
+            self.encode_group(&self.pending, output);
+            self.pending_len = 0;
+        }
+
+        let mut groups = input.chunks_exact(4);
+        for group in &mut groups {
+            self.encode_group(group, output);
+        }
+        let rest = groups.remainder();
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.pending_len = rest.len();
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) {
+        if self.pending_len > 0 {
+            self.encode_group(&self.pending[..self.pending_len], output);
+            self.pending_len = 0;
+        }
+    }
+}
+
+/// Incremental decoder shared by [`decode_with`] and
+/// [`Ascii85Reader`](stream::Ascii85Reader).
+pub struct Decoder {
+    variant: Variant,
+    group: [u32; GROUP],
+    group_offset: usize,
+    n: usize,
+    symbols: usize,
+    offset: usize,
+}
+
+impl Decoder {
+    pub fn new(variant: Variant) -> Self {
+        Decoder {
+            variant,
+            group: [0; GROUP],
+            group_offset: 0,
+            n: 0,
+            symbols: 0,
+            offset: 0,
+        }
+    }
+
+    /// Decodes `len` symbols of the current group, padding with the highest
+    /// digit so truncated groups round-trip.
+    fn decode_group(&mut self, len: usize, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.group[len..].fill(84);
+        let n = self.group.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+        let n = u32::try_from(n).map_err(|_| DecodeError::Overflow {
+            offset: self.group_offset,
+        })?;
+        output.extend_from_slice(&n.to_be_bytes()[..len - 1]);
+        Ok(())
+    }
+}
+
+impl stream::Decode for Decoder {
+    type Error = DecodeError;
+
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for &b in input {
+            let offset = self.offset;
+            self.offset += 1;
+
+            if self.variant == Variant::Ascii85 {
+                if b.is_ascii_whitespace() {
+                    continue;
+                }
+                if b == b'z' && self.n == 0 {
+                    self.symbols += 1;
+                    output.extend_from_slice(&[0; 4]);
+                    continue;
+                }
+            }
+
+            let digit = self
+                .variant
+                .digit(b)
+                .ok_or(DecodeError::invalid_byte(b, offset))?;
+            if self.n == 0 {
+                self.group_offset = offset;
+            }
+            self.group[self.n] = digit;
+            self.n += 1;
+            self.symbols += 1;
+
+            if self.n == GROUP {
+                self.decode_group(GROUP, output)?;
+                self.n = 0;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match self.n {
+            0 => {}
+            1 => return Err(DecodeError::InvalidLength(self.symbols)),
+            n => self.decode_group(n, output)?,
+        }
+        self.n = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{DecodeError, ascii85::*};
+
+    #[test]
+    fn test_ascii85_vectors() {
+        let vectors: [(&[u8], &str); 6] = [
+            (b"", ""),
+            (b"Man ", "9jqo^"),
+            (b"sure.", "F*2M7/c"),
+            (b"Man is distinguished", "9jqo^BlbD-BleB1DJ+*+F(f,q"),
+            (&[0, 0, 0, 0, 0], "z!!"),
+            (&[0xff; 4], "s8W-!"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encode(plain), encoded);
+            assert_eq!(decode(encoded), Ok(plain.to_vec()));
+        }
+        assert_eq!(decode("9jqo^ \nF*2M7/c"), Ok(b"Man sure.".to_vec()));
+    }
+
+    #[test]
+    fn test_z85_vectors() {
+        // From the ZeroMQ RFC 32 reference implementation.
+        let plain = [0x86, 0x4f, 0xd2, 0x6f, 0xb5, 0x59, 0xf7, 0x5b];
+        assert_eq!(encode_with(&plain, Variant::Z85), "HelloWorld");
+        assert_eq!(decode_with("HelloWorld", Variant::Z85), Ok(plain.to_vec()));
+        assert_eq!(encode_with(&[0; 4], Variant::Z85), "00000");
+
+        for len in 0..12 {
+            let input: Vec<u8> = (0..len).map(|i: u32| (i * 37 + 1) as u8).collect();
+            let encoded = encode_with(&input, Variant::Z85);
+            assert_eq!(decode_with(&encoded, Variant::Z85), Ok(input));
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("9jqo~"),
+            Err(DecodeError::InvalidChar { c: '~', offset: 4 })
+        );
+        assert_eq!(
+            decode("9jzo^"),
+            Err(DecodeError::InvalidChar { c: 'z', offset: 2 })
+        );
+        assert_eq!(decode("9jqo^9"), Err(DecodeError::InvalidLength(6)));
+        assert_eq!(decode("s8W-\""), Err(DecodeError::Overflow { offset: 0 }));
+        assert_eq!(
+            decode_with("Hello World", Variant::Z85),
+            Err(DecodeError::InvalidChar { c: ' ', offset: 5 })
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use crate::encoding::{DecodeError, stream};
+
+const STANDARD_TABLE: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const HEX_TABLE: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+const PAD: u8 = b'=';
+const INVALID: u8 = 0xff;
+
+const fn decode_table(table: &[u8; 32]) -> [u8; 256] {
+    let mut map = [INVALID; 256];
+    let mut i = 0;
+    while i < table.len() {
+        map[table[i] as usize] = i as u8;
+        i += 1;
+    }
+    map
+}
+
+const STANDARD_DECODE: [u8; 256] = decode_table(STANDARD_TABLE);
+const HEX_DECODE: [u8; 256] = decode_table(HEX_TABLE);
+
+/// Symbols per 5-byte input group.
+const GROUP: usize = 8;
+
+/// Valid symbol counts for a final group of 1 to 4 bytes.
+fn partial_group_ok(symbols: usize) -> bool {
+    matches!(symbols, 2 | 4 | 5 | 7)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alphabet {
+    /// RFC 4648 §6.
+    #[default]
+    Standard,
+    /// RFC 4648 §7 "base32hex", which preserves sort order.
+    Hex,
+}
+
+impl Alphabet {
+    fn encode_table(self) -> &'static [u8; 32] {
+        match self {
+            Alphabet::Standard => STANDARD_TABLE,
+            Alphabet::Hex => HEX_TABLE,
+        }
+    }
+
+    fn decode_table(self) -> &'static [u8; 256] {
+        match self {
+            Alphabet::Standard => &STANDARD_DECODE,
+            Alphabet::Hex => &HEX_DECODE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub alphabet: Alphabet,
+    /// Emit `=` padding when encoding; require it when decoding.
+    pub pad: bool,
+    /// Skip all ASCII whitespace when decoding.
+    pub ignore_whitespace: bool,
+}
+
+impl Config {
+    pub const STANDARD: Config = Config {
+        alphabet: Alphabet::Standard,
+        pad: true,
+        ignore_whitespace: false,
+    };
+
+    pub const STANDARD_NO_PAD: Config = Config::STANDARD.with_padding(false);
+
+    pub const HEX: Config = Config::STANDARD.with_alphabet(Alphabet::Hex);
+
+    pub const fn with_alphabet(self, alphabet: Alphabet) -> Config {
+        Config { alphabet, ..self }
+    }
+
+    pub const fn with_padding(self, pad: bool) -> Config {
+        Config { pad, ..self }
+    }
+
+    pub const fn with_ignore_whitespace(self, ignore_whitespace: bool) -> Config {
+        Config {
+            ignore_whitespace,
+            ..self
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::STANDARD
+    }
+}
+
+pub fn encode(input: &[u8]) -> String {
+    encode_with(input, Config::STANDARD)
+}
+
+pub fn encode_with(input: &[u8], config: Config) -> String {
+    use stream::Encode;
+
+    let mut output = Vec::with_capacity(input.len().div_ceil(5) * GROUP);
+    let mut encoder = Encoder::new(config);
+    encoder.update(input, &mut output);
+    encoder.finish(&mut output);
+    String::from_utf8(output).expect("base32 output is ASCII")
+}
+
+pub fn decode(input: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_with(input, Config::STANDARD)
+}
+
+pub fn decode_with(input: &str, config: Config) -> Result<Vec<u8>, DecodeError> {
+    use stream::Decode;
+
+    let mut output = Vec::with_capacity(input.len() / GROUP * 5);
+    let mut decoder = Decoder::new(config);
+    decoder
+        .update(input.as_bytes(), &mut output)
+        .and_then(|_| decoder.finish(&mut output))
+        .map_err(|e| e.with_input_char(input))?;
+    Ok(output)
+}
+
+/// Incremental encoder behind [`Base32Writer`](stream::Base32Writer).
+pub struct Encoder {
+    config: Config,
+    pending: [u8; 5],
+    pending_len: usize,
+}
+
+impl Encoder {
+    pub fn new(config: Config) -> Self {
+        Encoder {
+            config,
+            pending: [0; 5],
+            pending_len: 0,
+        }
+    }
+
+    fn encode_group(&self, group: &[u8], output: &mut Vec<u8>) {
+        let table = self.config.alphabet.encode_table();
+        let mut buffer = [0u8; 8];
+        buffer[3..3 + group.len()].copy_from_slice(group);
+        let n = u64::from_be_bytes(buffer);
+
+        let symbols = (group.len() * 8).div_ceil(5);
+        for i in 0..symbols {
+            output.push(table[(n >> (35 - 5 * i)) as usize & 0x1f]);
+        }
+        if self.config.pad {
+            output.resize(output.len() + GROUP - symbols, PAD);
+        }
+    }
+}
+
+impl stream::Encode for Encoder {
+    fn update(&mut self, mut input: &[u8], output: &mut Vec<u8>) {
+        if self.pending_len > 0 {
+            let take = (5 - self.pending_len).min(input.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&input[..take]);
+            self.pending_len += take;
+            input = &input[take..];
+            if self.pending_len < 5 {
+                return;
+            }
+            self.encode_group(&self.pending, output);
+            self.pending_len = 0;
+        }
+
+        let mut groups = input.chunks_exact(5);
+        for group in &mut groups {
+            self.encode_group(group, output);
+        }
+        let rest = groups.remainder();
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.pending_len = rest.len();
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) {
+        if self.pending_len > 0 {
+            self.encode_group(&self.pending[..self.pending_len], output);
+            self.pending_len = 0;
+        }
+    }
+}
+
+/// Incremental decoder shared by [`decode_with`] and
+/// [`Base32Reader`](stream::Base32Reader).
+pub struct Decoder {
+    config: Config,
+    group: [u8; GROUP],
+    last_offset: usize,
+    n: usize,
+    /// Data symbols in the current group once padding has started.
+    data: Option<usize>,
+    symbols: usize,
+    offset: usize,
+    finished: bool,
+}
+
+impl Decoder {
+    pub fn new(config: Config) -> Self {
+        Decoder {
+            config,
+            group: [0; GROUP],
+            last_offset: 0,
+            n: 0,
+            data: None,
+            symbols: 0,
+            offset: 0,
+            finished: false,
+        }
+    }
+
+    /// Decodes the first `len` symbols of the current group, rejecting
+    /// non-canonical encodings whose discarded low bits are set.
+    fn decode_group(&self, len: usize, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let n = self.group[..len]
+            .iter()
+            .fold(0u64, |acc, &v| (acc << 5) | v as u64)
+            << (5 * (GROUP - len));
+
+        let bytes = len * 5 / 8;
+        let unused = len * 5 - bytes * 8;
+        if (n >> (40 - len * 5)) & ((1 << unused) - 1) != 0 {
+            return Err(DecodeError::TrailingBits {
+                offset: self.last_offset,
+            });
+        }
+        output.extend_from_slice(&n.to_be_bytes()[3..3 + bytes]);
+        Ok(())
+    }
+}
+
+impl stream::Decode for Decoder {
+    type Error = DecodeError;
+
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let decode_table = self.config.alphabet.decode_table();
+
+        for &b in input {
+            let offset = self.offset;
+            self.offset += 1;
+
+            if self.config.ignore_whitespace && b.is_ascii_whitespace() {
+                continue;
+            }
+            if self.finished {
+                return Err(DecodeError::InvalidPadding { offset });
+            }
+            self.symbols += 1;
+
+            if b == PAD {
+                if !self.config.pad || (self.data.is_none() && !partial_group_ok(self.n)) {
+                    return Err(DecodeError::InvalidPadding { offset });
+                }
+                self.data.get_or_insert(self.n);
+            } else {
+                if self.data.is_some() {
+                    return Err(DecodeError::InvalidPadding { offset });
+                }
+                let val = decode_table[b as usize];
+                if val == INVALID {
+                    return Err(DecodeError::invalid_byte(b, offset));
+                }
+                self.group[self.n] = val;
+                self.last_offset = offset;
+            }
+            self.n += 1;
+
+            if self.n == GROUP {
+                self.decode_group(self.data.unwrap_or(GROUP), output)?;
+                self.finished = self.data.is_some();
+                self.n = 0;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match self.n {
+            0 => {}
+            n if !self.config.pad && partial_group_ok(n) => self.decode_group(n, output)?,
+            _ => return Err(DecodeError::InvalidLength(self.symbols)),
+        }
+        self.n = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{DecodeError, base32::*};
+
+    const RFC_VECTORS: [(&str, &str, &str); 7] = [
+        ("", "", ""),
+        ("f", "MY======", "CO======"),
+        ("fo", "MZXQ====", "CPNG===="),
+        ("foo", "MZXW6===", "CPNMU==="),
+        ("foob", "MZXW6YQ=", "CPNMUOG="),
+        ("fooba", "MZXW6YTB", "CPNMUOJ1"),
+        ("foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+    ];
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        for (plain, encoded, hex_encoded) in RFC_VECTORS {
+            assert_eq!(encode(plain.as_bytes()), encoded);
+            assert_eq!(decode(encoded), Ok(plain.as_bytes().to_vec()));
+            assert_eq!(encode_with(plain.as_bytes(), Config::HEX), hex_encoded);
+            assert_eq!(
+                decode_with(hex_encoded, Config::HEX),
+                Ok(plain.as_bytes().to_vec())
+            );
+
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(
+                encode_with(plain.as_bytes(), Config::STANDARD_NO_PAD),
+                unpadded
+            );
+            assert_eq!(
+                decode_with(unpadded, Config::STANDARD_NO_PAD),
+                Ok(plain.as_bytes().to_vec())
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("MZXW1==="),
+            Err(DecodeError::InvalidChar { c: '1', offset: 4 })
+        );
+        assert_eq!(decode("MZXW6"), Err(DecodeError::InvalidLength(5)));
+        assert_eq!(
+            decode("MZX====="),
+            Err(DecodeError::InvalidPadding { offset: 3 })
+        );
+        assert_eq!(
+            decode("MY===A=="),
+            Err(DecodeError::InvalidPadding { offset: 5 })
+        );
+        assert_eq!(
+            decode("MY======MY======"),
+            Err(DecodeError::InvalidPadding { offset: 8 })
+        );
+        assert_eq!(
+            decode("MZ======"),
+            Err(DecodeError::TrailingBits { offset: 1 })
+        );
+        assert_eq!(
+            decode_with("MZX", Config::STANDARD_NO_PAD),
+            Err(DecodeError::InvalidLength(3))
+        );
+        assert_eq!(
+            decode_with("MZXW 6YTB\n", Config::STANDARD.with_ignore_whitespace(true)),
+            Ok(b"fooba".to_vec())
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use crate::encoding::{DecodeError, stream};
+
+/// The Bitcoin alphabet: no `0`, `O`, `I` or `l`.
+const TABLE: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const INVALID: u8 = 0xff;
+
+const DECODE_TABLE: [u8; 256] = {
+    let mut map = [INVALID; 256];
+    let mut i = 0;
+    while i < TABLE.len() {
+        map[TABLE[i] as usize] = i as u8;
+        i += 1;
+    }
+    map
+};
+
+/// Leading zero bytes map one-to-one onto leading `1`s; the rest is a
+/// big-endian number converted by schoolbook division.
+pub fn encode(input: &[u8]) -> String {
+    let zeros = input.iter().take_while(|&&b| b == 0).count();
+
+    // Little-endian base58 digits of the number.
+    let mut digits: Vec<u8> = Vec::with_capacity(input.len() * 138 / 100 + 1);
+    for &b in &input[zeros..] {
+        let mut carry = b as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut output = String::with_capacity(zeros + digits.len());
+    output.extend(std::iter::repeat_n('1', zeros));
+    output.extend(digits.iter().rev().map(|&d| TABLE[d as usize] as char));
+    output
+}
+
+pub fn decode(input: &str) -> Result<Vec<u8>, DecodeError> {
+    use stream::Decode;
+
+    let mut output = Vec::with_capacity(input.len());
+    let mut decoder = Decoder::new();
+    decoder
+        .update(input.as_bytes(), &mut output)
+        .and_then(|_| decoder.finish(&mut output))
+        .map_err(|e| e.with_input_char(input))?;
+    Ok(output)
+}
+
+fn decode_digits(digits: &[u8], output: &mut Vec<u8>) {
+    let zeros = digits.iter().take_while(|&&d| d == 0).count();
+
+    // Little-endian bytes of the number.
+    let mut bytes: Vec<u8> = Vec::with_capacity(digits.len() * 733 / 1000 + 1);
+    for &d in &digits[zeros..] {
+        let mut carry = d as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    output.extend(std::iter::repeat_n(0, zeros));
+    output.extend(bytes.iter().rev());
+}
+
+/// Encoder behind [`Base58Writer`](stream::Base58Writer). Base58 has no
+/// groups, so all input is held until [`finish`](stream::Encode::finish).
+#[derive(Default)]
+pub struct Encoder {
+    pending: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+}
+
+impl stream::Encode for Encoder {
+    fn update(&mut self, input: &[u8], _output: &mut Vec<u8>) {
+        self.pending.extend_from_slice(input);
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) {
+        output.extend_from_slice(encode(&std::mem::take(&mut self.pending)).as_bytes());
+    }
+}
+
+/// Decoder behind [`decode`] and [`Base58Reader`](stream::Base58Reader).
+/// Symbols are validated as they arrive but only converted at the end.
+#[derive(Default)]
+pub struct Decoder {
+    digits: Vec<u8>,
+    offset: usize,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Decoder::default()
+    }
+}
+
+impl stream::Decode for Decoder {
+    type Error = DecodeError;
+
+    fn update(&mut self, input: &[u8], _output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        for &b in input {
+            let val = DECODE_TABLE[b as usize];
+            if val == INVALID {
+                return Err(DecodeError::invalid_byte(b, self.offset));
+            }
+            self.digits.push(val);
+            self.offset += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        decode_digits(&std::mem::take(&mut self.digits), output);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{DecodeError, base58::*, hex};
+
+    // draft-msporny-base58 test vectors.
+    #[test]
+    fn test_vectors() {
+        let vectors: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (
+                b"The quick brown fox jumps over the lazy dog.",
+                "USm3fpXnKG5EUBx2ndxBDMPVciP5hGey2Jh4NDv6gmeo1LkMeiKrLJUUBk6Z",
+            ),
+            (&[0x00, 0x00, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4"),
+            (&[0x00], "1"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encode(plain), encoded);
+            assert_eq!(decode(encoded), Ok(plain.to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_bitcoin_address() {
+        let payload = hex::decode("00f54a5851e9372b87810a8e60cdd2e7cfd80b6e31c7f18fe8").unwrap();
+        assert_eq!(encode(&payload), "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs");
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("2NEpo0TZ"),
+            Err(DecodeError::InvalidChar { c: '0', offset: 5 })
+        );
+        assert_eq!(
+            decode("abcl"),
+            Err(DecodeError::InvalidChar { c: 'l', offset: 3 })
+        );
+        assert_eq!(
+            decode("ab é"),
+            Err(DecodeError::InvalidChar { c: ' ', offset: 2 })
+        );
+    }
+}
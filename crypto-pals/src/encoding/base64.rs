@@ -0,0 +1,592 @@
+use crate::encoding::{DecodeError, stream};
+
+const STANDARD_TABLE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE_TABLE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const PAD: u8 = b'=';
+const INVALID: u8 = 0xff;
+const MIME_LINE_LEN: usize = 76;
+
+const fn decode_table(table: &[u8; 64]) -> [u8; 256] {
+    let mut map = [INVALID; 256];
+    let mut i = 0;
+    while i < table.len() {
+        map[table[i] as usize] = i as u8;
+        i += 1;
+    }
+    map
+}
+
+const STANDARD_DECODE: [u8; 256] = decode_table(STANDARD_TABLE);
+const URL_SAFE_DECODE: [u8; 256] = decode_table(URL_SAFE_TABLE);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alphabet {
+    /// RFC 4648 §4: `+` and `/`.
+    #[default]
+    Standard,
+    /// RFC 4648 §5: `-` and `_`, safe in URLs and file names.
+    UrlSafe,
+}
+
+impl Alphabet {
+    fn encode_table(self) -> &'static [u8; 64] {
+        match self {
+            Alphabet::Standard => STANDARD_TABLE,
+            Alphabet::UrlSafe => URL_SAFE_TABLE,
+        }
+    }
+
+    fn decode_table(self) -> &'static [u8; 256] {
+        match self {
+            Alphabet::Standard => &STANDARD_DECODE,
+            Alphabet::UrlSafe => &URL_SAFE_DECODE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub alphabet: Alphabet,
+    /// Emit `=` padding when encoding; require it when decoding. Without
+    /// padding, a `=` in the input is rejected.
+    pub pad: bool,
+    /// Wrap encoded output every `n` symbols with CRLF, as MIME does. When
+    /// decoding, CR and LF are skipped wherever they appear.
+    pub line_wrap: Option<usize>,
+    /// Skip all ASCII whitespace when decoding.
+    pub ignore_whitespace: bool,
+}
+
+impl Config {
+    pub const STANDARD: Config = Config {
+        alphabet: Alphabet::Standard,
+        pad: true,
+        line_wrap: None,
+        ignore_whitespace: false,
+    };
+
+    pub const STANDARD_NO_PAD: Config = Config::STANDARD.with_padding(false);
+
+    pub const URL_SAFE: Config = Config::STANDARD.with_alphabet(Alphabet::UrlSafe);
+
+    pub const URL_SAFE_NO_PAD: Config = Config::URL_SAFE.with_padding(false);
+
+    /// RFC 2045: standard alphabet, padded, 76 symbols per line.
+    pub const MIME: Config = Config::STANDARD.with_line_wrap(Some(MIME_LINE_LEN));
+
+    pub const fn with_alphabet(self, alphabet: Alphabet) -> Config {
+        Config { alphabet, ..self }
+    }
+
+    pub const fn with_padding(self, pad: bool) -> Config {
+        Config { pad, ..self }
+    }
+
+    pub const fn with_line_wrap(self, line_wrap: Option<usize>) -> Config {
+        Config { line_wrap, ..self }
+    }
+
+    pub const fn with_ignore_whitespace(self, ignore_whitespace: bool) -> Config {
+        Config {
+            ignore_whitespace,
+            ..self
+        }
+    }
+
+    fn skips(&self, b: u8) -> bool {
+        (self.ignore_whitespace && b.is_ascii_whitespace())
+            || (self.line_wrap.is_some() && (b == b'\r' || b == b'\n'))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::STANDARD
+    }
+}
+
+pub fn encode(input: &[u8]) -> String {
+    encode_with(input, Config::STANDARD)
+}
+
+pub fn encode_with(input: &[u8], config: Config) -> String {
+    let mut output = Vec::with_capacity(encoded_len(input.len(), config));
+    encode_to_vec(input, &mut output, config);
+    String::from_utf8(output).expect("base64 output is ASCII")
+}
+
+/// Length of the encoding of `input_len` bytes, line breaks included.
+pub fn encoded_len(input_len: usize, config: Config) -> usize {
+    let symbols = symbols_len(input_len, config.pad);
+    symbols + line_breaks(symbols, config) * 2
+}
+
+fn symbols_len(input_len: usize, pad: bool) -> usize {
+    if pad {
+        input_len.div_ceil(3) * 4
+    } else {
+        (input_len * 4).div_ceil(3)
+    }
+}
+
+fn line_breaks(symbols: usize, config: Config) -> usize {
+    match config.line_wrap {
+        Some(width) if width > 0 => symbols.saturating_sub(1) / width,
+        _ => 0,
+    }
+}
+
+/// Appends the encoding of `input` to `output`.
+pub fn encode_to_vec(input: &[u8], output: &mut Vec<u8>, config: Config) {
+    let start = output.len();
+    output.resize(start + encoded_len(input.len(), config), 0);
+    encode_slice(input, &mut output[start..], config);
+}
+
+/// Encodes `input` into the front of `output`, returning the number of bytes
+/// written.
+///
+/// # Panics
+///
+/// If `output` is shorter than [`encoded_len`].
+pub fn encode_slice(input: &[u8], output: &mut [u8], config: Config) -> usize {
+    let len = encoded_len(input.len(), config);
+    assert!(
+        output.len() >= len,
+        "base64 output buffer too small: {} < {len}",
+        output.len()
+    );
+
+    let symbols = encode_symbols(input, output, config.alphabet.encode_table(), config.pad);
+
+    // Spread the lines out from the back so every move lands on bytes that
+    // have already been relocated.
+    if let Some(width) = config.line_wrap.filter(|&width| width > 0) {
+        for line in (1..=line_breaks(symbols, config)).rev() {
+            let src = line * width..((line + 1) * width).min(symbols);
+            let dst = src.start + line * 2;
+            output.copy_within(src, dst);
+            output[dst - 2..dst].copy_from_slice(b"\r\n");
+        }
+    }
+    len
+}
+
+/// Unwrapped encode into `output`, returning the number of symbols written.
+fn encode_symbols(input: &[u8], output: &mut [u8], table: &[u8; 64], pad: bool) -> usize {
+    let full = input.len() / 3;
+    let (head, tail) = output.split_at_mut(full * 4);
+
+    for (chunk, out) in input.chunks_exact(3).zip(head.chunks_exact_mut(4)) {
+        let n = (chunk[0] as u32) << 16 | (chunk[1] as u32) << 8 | chunk[2] as u32;
+        out[0] = table[(n >> 18) as usize & 0x3f];
+        out[1] = table[(n >> 12) as usize & 0x3f];
+        out[2] = table[(n >> 6) as usize & 0x3f];
+        out[3] = table[n as usize & 0x3f];
+    }
+
+    let written = match input[full * 3..] {
+        [a] => {
+            tail[0] = table[(a >> 2) as usize];
+            tail[1] = table[((a & 0x03) << 4) as usize];
+            2
+        }
+        [a, b] => {
+            tail[0] = table[(a >> 2) as usize];
+            tail[1] = table[(((a & 0x03) << 4) | (b >> 4)) as usize];
+            tail[2] = table[((b & 0x0f) << 2) as usize];
+            3
+        }
+        _ => return full * 4,
+    };
+
+    if pad {
+        tail[written..4].fill(PAD);
+        full * 4 + 4
+    } else {
+        full * 4 + written
+    }
+}
+
+/// Incremental encoder behind [`Base64Writer`](stream::Base64Writer).
+pub struct Encoder {
+    config: Config,
+    pending: [u8; 3],
+    pending_len: usize,
+    column: usize,
+}
+
+impl Encoder {
+    /// Input bytes encoded per step when line wrapping goes through scratch.
+    const BATCH: usize = 768;
+
+    pub fn new(config: Config) -> Self {
+        Encoder {
+            config,
+            pending: [0; 3],
+            pending_len: 0,
+            column: 0,
+        }
+    }
+
+    /// `input` must be a whole number of 3-byte groups unless it is the
+    /// final tail of the stream.
+    fn encode_batch(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        let table = self.config.alphabet.encode_table();
+
+        if !matches!(self.config.line_wrap, Some(width) if width > 0) {
+            let start = output.len();
+            output.resize(start + symbols_len(input.len(), self.config.pad), 0);
+            encode_symbols(input, &mut output[start..], table, self.config.pad);
+            return;
+        }
+
+        let mut scratch = [0u8; Self::BATCH / 3 * 4];
+        for batch in input.chunks(Self::BATCH) {
+            let n = encode_symbols(batch, &mut scratch, table, self.config.pad);
+            self.extend_wrapped(&scratch[..n], output);
+        }
+    }
+
+    fn extend_wrapped(&mut self, mut symbols: &[u8], output: &mut Vec<u8>) {
+        let width = self.config.line_wrap.unwrap_or(usize::MAX);
+        while !symbols.is_empty() {
+            if self.column == width {
+                output.extend_from_slice(b"\r\n");
+                self.column = 0;
+            }
+            let take = (width - self.column).min(symbols.len());
+            output.extend_from_slice(&symbols[..take]);
+            self.column += take;
+            symbols = &symbols[take..];
+        }
+    }
+}
+
+impl stream::Encode for Encoder {
+    fn update(&mut self, mut input: &[u8], output: &mut Vec<u8>) {
+        if self.pending_len > 0 {
+            let take = (3 - self.pending_len).min(input.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&input[..take]);
+            self.pending_len += take;
+            input = &input[take..];
+            if self.pending_len < 3 {
+                return;
+            }
+            let pending = self.pending;
+            self.encode_batch(&pending, output);
+            self.pending_len = 0;
+        }
+
+        let full = input.len() / 3 * 3;
+        self.encode_batch(&input[..full], output);
+
+        let rest = &input[full..];
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.pending_len = rest.len();
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) {
+        if self.pending_len > 0 {
+            let pending = self.pending;
+            self.encode_batch(&pending[..self.pending_len], output);
+            self.pending_len = 0;
+        }
+    }
+}
+
+/// Strict RFC 4648 decode: standard alphabet, mandatory padding, no whitespace.
+pub fn decode(input: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_with(input, Config::STANDARD)
+}
+
+pub fn decode_with(input: &str, config: Config) -> Result<Vec<u8>, DecodeError> {
+    use stream::Decode;
+
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut decoder = Decoder::new(config);
+
+    decoder
+        .update(input.as_bytes(), &mut output)
+        .and_then(|_| decoder.finish(&mut output))
+        .map_err(|e| e.with_input_char(input))?;
+    Ok(output)
+}
+
+/// Incremental decoder shared by [`decode_with`] and
+/// [`Base64Reader`](stream::Base64Reader).
+pub struct Decoder {
+    config: Config,
+    quad: [u8; 4],
+    offsets: [usize; 4],
+    n: usize,
+    pads: usize,
+    symbols: usize,
+    offset: usize,
+    finished: bool,
+}
+
+impl Decoder {
+    pub fn new(config: Config) -> Self {
+        Decoder {
+            config,
+            quad: [0; 4],
+            offsets: [0; 4],
+            n: 0,
+            pads: 0,
+            symbols: 0,
+            offset: 0,
+            finished: false,
+        }
+    }
+}
+
+impl stream::Decode for Decoder {
+    type Error = DecodeError;
+
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let decode_table = self.config.alphabet.decode_table();
+
+        for &b in input {
+            let offset = self.offset;
+            self.offset += 1;
+
+            if self.config.skips(b) {
+                continue;
+            }
+            if self.finished {
+                return Err(DecodeError::InvalidPadding { offset });
+            }
+            self.symbols += 1;
+
+            if b == PAD {
+                if !self.config.pad || self.n < 2 {
+                    return Err(DecodeError::InvalidPadding { offset });
+                }
+                self.pads += 1;
+            } else {
+                if self.pads > 0 {
+                    // Data after a `=` in the middle of the final quad.
+                    return Err(DecodeError::InvalidPadding { offset });
+                }
+                let val = decode_table[b as usize];
+                if val == INVALID {
+                    return Err(DecodeError::invalid_byte(b, offset));
+                }
+                self.quad[self.n] = val;
+            }
+            self.offsets[self.n] = offset;
+            self.n += 1;
+
+            if self.n == 4 {
+                decode_quad(&self.quad, &self.offsets, 4 - self.pads, output)?;
+                self.finished = self.pads > 0;
+                self.n = 0;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match self.n {
+            0 => {}
+            2 | 3 if !self.config.pad => decode_quad(&self.quad, &self.offsets, self.n, output)?,
+            _ => return Err(DecodeError::InvalidLength(self.symbols)),
+        }
+        self.n = 0;
+        Ok(())
+    }
+}
+
+/// Decodes the first `len` sextets of `quad` (2, 3 or 4), rejecting
+/// non-canonical encodings whose discarded low bits are set.
+fn decode_quad(
+    quad: &[u8; 4],
+    offsets: &[usize; 4],
+    len: usize,
+    output: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    let [b0, b1, b2, b3] = *quad;
+
+    match len {
+        2 if b1 & 0x0f != 0 => return Err(DecodeError::TrailingBits { offset: offsets[1] }),
+        3 if b2 & 0x03 != 0 => return Err(DecodeError::TrailingBits { offset: offsets[2] }),
+        _ => {}
+    }
+
+    output.push((b0 << 2) | (b1 >> 4));
+    if len > 2 {
+        output.push(((b1 & 0x0f) << 4) | (b2 >> 2));
+    }
+    if len > 3 {
+        output.push(((b2 & 0x03) << 6) | b3);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::base64::*;
+
+    const RFC_VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        for (plain, encoded) in RFC_VECTORS {
+            assert_eq!(encode(plain.as_bytes()), encoded);
+            assert_eq!(decode(encoded), Ok(plain.as_bytes().to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("Zm9v!A=="),
+            Err(DecodeError::InvalidChar { c: '!', offset: 4 })
+        );
+        assert_eq!(decode("Zm9vY"), Err(DecodeError::InvalidLength(5)));
+        assert_eq!(decode("Zg="), Err(DecodeError::InvalidLength(3)));
+        assert_eq!(
+            decode("Z==="),
+            Err(DecodeError::InvalidPadding { offset: 1 })
+        );
+        assert_eq!(
+            decode("Zg=a"),
+            Err(DecodeError::InvalidPadding { offset: 3 })
+        );
+        assert_eq!(
+            decode("Zg==Zg=="),
+            Err(DecodeError::InvalidPadding { offset: 4 })
+        );
+        assert_eq!(decode("Zh=="), Err(DecodeError::TrailingBits { offset: 1 }));
+        assert_eq!(decode("Zm9="), Err(DecodeError::TrailingBits { offset: 2 }));
+        assert_eq!(
+            decode("Zm9v\nYg=="),
+            Err(DecodeError::InvalidChar { c: '\n', offset: 4 })
+        );
+    }
+
+    #[test]
+    fn test_decode_ignore_whitespace() {
+        let config = Config::STANDARD.with_ignore_whitespace(true);
+        assert_eq!(
+            decode_with("Zm9v\nYmFy\r\n", config),
+            Ok(b"foobar".to_vec())
+        );
+        assert_eq!(decode_with(" Zm 9v Yg = = ", config), Ok(b"foob".to_vec()));
+        assert_eq!(
+            decode_with("Zg==\nZg==", config),
+            Err(DecodeError::InvalidPadding { offset: 5 })
+        );
+    }
+
+    #[test]
+    fn test_url_safe() {
+        let input = [0xfb, 0xff, 0xbf, 0xfe];
+        assert_eq!(encode(&input), "+/+//g==");
+        assert_eq!(encode_with(&input, Config::URL_SAFE), "-_-__g==");
+        assert_eq!(encode_with(&input, Config::URL_SAFE_NO_PAD), "-_-__g");
+
+        assert_eq!(
+            decode_with("-_-__g==", Config::URL_SAFE),
+            Ok(input.to_vec())
+        );
+        assert_eq!(
+            decode_with("-_-__g", Config::URL_SAFE_NO_PAD),
+            Ok(input.to_vec())
+        );
+        assert_eq!(
+            decode_with("+/+//g==", Config::URL_SAFE),
+            Err(DecodeError::InvalidChar { c: '+', offset: 0 })
+        );
+    }
+
+    #[test]
+    fn test_no_pad() {
+        let config = Config::STANDARD_NO_PAD;
+        for (plain, encoded) in RFC_VECTORS {
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(encode_with(plain.as_bytes(), config), unpadded);
+            assert_eq!(decode_with(unpadded, config), Ok(plain.as_bytes().to_vec()));
+        }
+        assert_eq!(
+            decode_with("Zg==", config),
+            Err(DecodeError::InvalidPadding { offset: 2 })
+        );
+        assert_eq!(
+            decode_with("Zm9vY", config),
+            Err(DecodeError::InvalidLength(5))
+        );
+        assert_eq!(
+            decode_with("Zh", config),
+            Err(DecodeError::TrailingBits { offset: 1 })
+        );
+    }
+
+    #[test]
+    fn test_encode_slice() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for config in [
+            Config::STANDARD,
+            Config::URL_SAFE_NO_PAD,
+            Config::MIME,
+            Config::STANDARD.with_line_wrap(Some(5)),
+        ] {
+            for len in 0..40 {
+                let mut buf = [0u8; 128];
+                let n = encode_slice(&input[..len], &mut buf, config);
+                assert_eq!(n, encoded_len(len, config));
+                assert_eq!(
+                    decode_with(std::str::from_utf8(&buf[..n]).unwrap(), config),
+                    Ok(input[..len].to_vec())
+                );
+            }
+
+            let mut out = b"prefix:".to_vec();
+            encode_to_vec(&input, &mut out, config);
+            assert_eq!(&out[7..], encode_with(&input, config).as_bytes());
+        }
+
+        let wrapped = encode_with(b"foobarfoobar", Config::STANDARD.with_line_wrap(Some(5)));
+        assert_eq!(wrapped, "Zm9vY\r\nmFyZm\r\n9vYmF\r\ny");
+    }
+
+    #[test]
+    #[should_panic(expected = "too small")]
+    fn test_encode_slice_short_buffer() {
+        encode_slice(b"foo", &mut [0u8; 3], Config::STANDARD);
+    }
+
+    #[test]
+    fn test_mime_wrap() {
+        let input: Vec<u8> = (0..=255).collect();
+        let encoded = encode_with(&input, Config::MIME);
+
+        let lines: Vec<&str> = encoded.split("\r\n").collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[..4].iter().all(|line| line.len() == 76));
+        assert_eq!(lines.concat(), encode(&input));
+        assert!(!encoded.ends_with("\r\n"));
+
+        assert_eq!(decode_with(&encoded, Config::MIME), Ok(input.clone()));
+        assert_eq!(
+            decode_with("Zm9v\nYmFy\n", Config::MIME),
+            Ok(b"foobar".to_vec())
+        );
+        assert_eq!(
+            decode_with("Zm9v YmFy", Config::MIME),
+            Err(DecodeError::InvalidChar { c: ' ', offset: 4 })
+        );
+        assert_eq!(encode_with(&input[..57], Config::MIME).len(), 76);
+    }
+}
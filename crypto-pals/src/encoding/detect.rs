@@ -0,0 +1,280 @@
+use std::{fs, io};
+
+use crate::encoding::{
+    DecodeError,
+    base64::{self, Config},
+    hex,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// One hex blob, possibly split across lines.
+    Hex,
+    /// One hex message per line, as in `static/4.txt`.
+    HexLines,
+    /// One standard base64 blob, possibly line-wrapped, as in `static/6.txt`.
+    Base64,
+    /// One standard base64 message per line.
+    Base64Lines,
+    /// One URL-safe base64 blob, padded or not.
+    Base64Url,
+    /// One URL-safe base64 message per line.
+    Base64UrlLines,
+    /// Anything else; used as-is.
+    Binary,
+}
+
+impl Encoding {
+    fn is_line_delimited(self) -> bool {
+        matches!(
+            self,
+            Encoding::HexLines | Encoding::Base64Lines | Encoding::Base64UrlLines
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub encoding: Encoding,
+    /// Rough likelihood in `0.0..=1.0`; comparable between candidates for the
+    /// same input, not calibrated across inputs.
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ciphertext {
+    pub encoding: Encoding,
+    /// One entry per line for line-delimited encodings, otherwise exactly one.
+    pub messages: Vec<Vec<u8>>,
+}
+
+const TEXT_ENCODINGS: [Encoding; 6] = [
+    Encoding::Hex,
+    Encoding::HexLines,
+    Encoding::Base64,
+    Encoding::Base64Lines,
+    Encoding::Base64Url,
+    Encoding::Base64UrlLines,
+];
+
+pub fn detect_encoding(data: &[u8]) -> Encoding {
+    score_encodings(data)[0].encoding
+}
+
+/// Every encoding `data` decodes under, most likely first. Always includes a
+/// [`Encoding::Binary`] fallback, ranked by how weak the best text match is.
+pub fn score_encodings(data: &[u8]) -> Vec<Candidate> {
+    let Some(lines) = text_lines(data) else {
+        return vec![Candidate {
+            encoding: Encoding::Binary,
+            confidence: 1.0,
+        }];
+    };
+
+    let symbols: usize = lines.iter().map(|line| line.len()).sum();
+    let is_hex = lines
+        .iter()
+        .all(|line| line.bytes().all(|b| b.is_ascii_hexdigit()));
+    let has_std = lines.iter().any(|line| line.contains(['+', '/']));
+    let has_url = lines.iter().any(|line| line.contains(['-', '_']));
+    let wrapped = lines.len() == 1 || is_wrapped(&lines);
+
+    let mut candidates: Vec<Candidate> = TEXT_ENCODINGS
+        .into_iter()
+        .filter(|&encoding| decode_lines(&lines, encoding).is_ok())
+        .map(|encoding| {
+            let mut confidence = match encoding {
+                Encoding::Hex | Encoding::HexLines if symbols >= 8 => 0.99,
+                Encoding::Hex | Encoding::HexLines => 0.75,
+                // Hex digits are a subset of both base64 alphabets.
+                Encoding::Base64 | Encoding::Base64Lines if is_hex => 0.2,
+                Encoding::Base64 | Encoding::Base64Lines if has_std || symbols >= 16 => 0.95,
+                Encoding::Base64 | Encoding::Base64Lines => 0.6,
+                Encoding::Base64Url | Encoding::Base64UrlLines if has_url => 0.95,
+                Encoding::Base64Url | Encoding::Base64UrlLines if is_hex => 0.1,
+                // Without `-` or `_` the standard alphabet is the better bet.
+                Encoding::Base64Url | Encoding::Base64UrlLines => 0.4,
+                Encoding::Binary => unreachable!(),
+            };
+
+            // Multi-line hex is nearly always one message per line; base64 is
+            // one wrapped blob when the lines have a fixed width.
+            let prefer_lines = lines.len() > 1 && (is_hex || !wrapped);
+            if encoding.is_line_delimited() != prefer_lines && lines.len() > 1 {
+                confidence *= 0.5;
+            }
+            Candidate {
+                encoding,
+                confidence,
+            }
+        })
+        .collect();
+
+    let best = candidates.iter().map(|c| c.confidence).fold(0.0, f64::max);
+    candidates.push(Candidate {
+        encoding: Encoding::Binary,
+        confidence: (1.0 - best).max(0.01),
+    });
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    candidates
+}
+
+/// Decodes `data` as `encoding`; one message per line for the line-delimited
+/// variants, one message otherwise.
+pub fn decode_as(data: &[u8], encoding: Encoding) -> Result<Vec<Vec<u8>>, DecodeError> {
+    if encoding == Encoding::Binary {
+        return Ok(vec![data.to_vec()]);
+    }
+    let text = std::str::from_utf8(data)
+        .map_err(|e| DecodeError::invalid_byte(data[e.valid_up_to()], e.valid_up_to()))?;
+    decode_lines(
+        &text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>(),
+        encoding,
+    )
+}
+
+/// Reads `path`, guesses its encoding and decodes it.
+pub fn load_ciphertext(path: &str) -> io::Result<Ciphertext> {
+    let data = fs::read(path)?;
+    let encoding = detect_encoding(&data);
+    let messages =
+        decode_as(&data, encoding).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Ciphertext { encoding, messages })
+}
+
+/// Non-empty trimmed lines, or `None` if `data` is not printable ASCII text.
+fn text_lines(data: &[u8]) -> Option<Vec<&str>> {
+    if !data
+        .iter()
+        .all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+    {
+        return None;
+    }
+    let text = std::str::from_utf8(data).ok()?;
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then_some(lines)
+}
+
+/// Fixed-width lines with only the last one shorter, as a wrapping encoder
+/// produces.
+fn is_wrapped(lines: &[&str]) -> bool {
+    let (last, body) = lines.split_last().expect("non-empty");
+    let width = body[0].len();
+    body.iter().all(|line| line.len() == width) && last.len() <= width
+}
+
+fn decode_lines(lines: &[&str], encoding: Encoding) -> Result<Vec<Vec<u8>>, DecodeError> {
+    if encoding.is_line_delimited() {
+        lines
+            .iter()
+            .map(|line| decode_one(line, encoding))
+            .collect()
+    } else {
+        decode_one(&lines.concat(), encoding).map(|message| vec![message])
+    }
+}
+
+fn decode_one(text: &str, encoding: Encoding) -> Result<Vec<u8>, DecodeError> {
+    match encoding {
+        Encoding::Hex | Encoding::HexLines => Ok(hex::decode(text)?),
+        Encoding::Base64 | Encoding::Base64Lines => decode_base64(text, Config::STANDARD),
+        Encoding::Base64Url | Encoding::Base64UrlLines => decode_base64(text, Config::URL_SAFE),
+        Encoding::Binary => Ok(text.as_bytes().to_vec()),
+    }
+}
+
+/// Accepts the padded form, or the unpadded one when no `=` is present.
+fn decode_base64(text: &str, config: Config) -> Result<Vec<u8>, DecodeError> {
+    match base64::decode_with(text, config) {
+        Err(DecodeError::InvalidLength(_)) if !text.contains('=') => {
+            base64::decode_with(text, config.with_padding(false))
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{base64, detect::*, hex};
+
+    #[test]
+    fn test_static_files() {
+        for (path, encoding, messages) in [
+            ("./static/4.txt", Encoding::HexLines, 327),
+            ("./static/6.txt", Encoding::Base64, 1),
+            ("./static/7.txt", Encoding::Base64, 1),
+            ("./static/8.txt", Encoding::HexLines, 204),
+        ] {
+            let ciphertext = load_ciphertext(path).unwrap();
+            assert_eq!(ciphertext.encoding, encoding, "{path}");
+            assert_eq!(ciphertext.messages.len(), messages, "{path}");
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let data: Vec<u8> = (0..=255).collect();
+
+        let std = base64::encode(&data);
+        assert_eq!(detect_encoding(std.as_bytes()), Encoding::Base64);
+
+        let url = base64::encode_with(&data, base64::Config::URL_SAFE_NO_PAD);
+        assert_eq!(detect_encoding(url.as_bytes()), Encoding::Base64Url);
+
+        let lines = format!("{}\n{}\n", base64::encode(b"one"), base64::encode(b"three"));
+        assert_eq!(detect_encoding(lines.as_bytes()), Encoding::Base64Lines);
+        assert_eq!(
+            decode_as(lines.as_bytes(), Encoding::Base64Lines),
+            Ok(vec![b"one".to_vec(), b"three".to_vec()])
+        );
+
+        let mime = base64::encode_with(&data, base64::Config::MIME);
+        assert_eq!(detect_encoding(mime.as_bytes()), Encoding::Base64);
+
+        assert_eq!(
+            detect_encoding(hex::encode(&data).as_bytes()),
+            Encoding::Hex
+        );
+        assert_eq!(detect_encoding(&data), Encoding::Binary);
+        assert_eq!(detect_encoding(b"not hex, not base64!"), Encoding::Binary);
+        assert_eq!(detect_encoding(b""), Encoding::Binary);
+    }
+
+    #[test]
+    fn test_scores_are_ranked() {
+        let candidates = score_encodings(b"deadbeef");
+        assert_eq!(candidates[0].encoding, Encoding::Hex);
+        assert!(candidates.iter().any(|c| c.encoding == Encoding::Base64));
+        assert_eq!(candidates.last().unwrap().encoding, Encoding::Binary);
+
+        // Only weak text matches, so the fallback outranks them.
+        let weak = score_encodings(b"abc");
+        assert_eq!(weak[0].encoding, Encoding::Binary);
+        assert_eq!(detect_encoding(b"abc"), Encoding::Binary);
+
+        for data in [
+            &b"deadbeef"[..],
+            b"abc",
+            b"SGVsbG8=",
+            b"a-b_c\nd-e_f\n",
+            b"\xff\x00",
+        ] {
+            let candidates = score_encodings(data);
+            assert!(
+                candidates
+                    .windows(2)
+                    .all(|w| w[0].confidence >= w[1].confidence),
+                "{data:?}"
+            );
+            assert!(candidates.iter().any(|c| c.encoding == Encoding::Binary));
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::{error::Error, fmt};
+
+use crate::encoding::{input_char, stream};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    /// The input held an odd number of hex digits.
+    OddLength(usize),
+    /// A character that is not a hex digit, at the given byte offset of the input.
+    InvalidChar { c: char, offset: usize },
+}
+
+impl HexError {
+    /// Reports the full character from `input` rather than its first UTF-8 byte.
+    pub(crate) fn with_input_char(self, input: &str) -> Self {
+        match self {
+            HexError::InvalidChar { offset, .. } => HexError::InvalidChar {
+                c: input_char(input, offset),
+                offset,
+            },
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::OddLength(digits) => write!(f, "odd number of hex digits: {digits}"),
+            HexError::InvalidChar { c, offset } => {
+                write!(f, "invalid hex character {c:?} at offset {offset}")
+            }
+        }
+    }
+}
+
+impl Error for HexError {}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Case {
+    #[default]
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HexOptions {
+    /// Skip ASCII whitespace anywhere in the input.
+    pub ignore_whitespace: bool,
+    /// Accept a `0x`/`0X` prefix at the start of the input and, when
+    /// whitespace is ignored, at the start of every whitespace separated token.
+    pub allow_prefix: bool,
+}
+
+impl HexOptions {
+    pub fn tolerant() -> Self {
+        HexOptions {
+            ignore_whitespace: true,
+            allow_prefix: true,
+        }
+    }
+}
+
+fn hex_val(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn byte_char(b: u8) -> char {
+    if b.is_ascii() { b as char } else { '\u{fffd}' }
+}
+
+/// Incremental decoder shared by [`decode_with`] and
+/// [`HexReader`](stream::HexReader).
+pub struct Decoder {
+    opts: HexOptions,
+    high: Option<u8>,
+    digits: usize,
+    offset: usize,
+    token_start: bool,
+    /// A token-leading `0` that may turn out to start a `0x` prefix.
+    pending_zero: bool,
+}
+
+impl Decoder {
+    pub fn new(opts: HexOptions) -> Self {
+        Decoder {
+            opts,
+            high: None,
+            digits: 0,
+            offset: 0,
+            token_start: true,
+            pending_zero: false,
+        }
+    }
+
+    fn digit(&mut self, val: u8, output: &mut Vec<u8>) {
+        self.digits += 1;
+        match self.high.take() {
+            Some(h) => output.push((h << 4) | val),
+            None => self.high = Some(val),
+        }
+    }
+}
+
+impl stream::Decode for Decoder {
+    type Error = HexError;
+
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), HexError> {
+        for &b in input {
+            let offset = self.offset;
+            self.offset += 1;
+
+            if std::mem::take(&mut self.pending_zero) {
+                if b == b'x' || b == b'X' {
+                    continue;
+                }
+                self.digit(0, output);
+            }
+
+            if self.opts.ignore_whitespace && b.is_ascii_whitespace() {
+                self.token_start = true;
+                continue;
+            }
+
+            let token_start = std::mem::replace(&mut self.token_start, false);
+            if self.opts.allow_prefix && token_start && b == b'0' {
+                self.pending_zero = true;
+                continue;
+            }
+
+            let val = hex_val(b).ok_or(HexError::InvalidChar {
+                c: byte_char(b),
+                offset,
+            })?;
+            self.digit(val, output);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), HexError> {
+        if std::mem::take(&mut self.pending_zero) {
+            self.digit(0, output);
+        }
+        if self.high.is_some() {
+            return Err(HexError::OddLength(self.digits));
+        }
+        Ok(())
+    }
+}
+
+/// Strict decode: only hex digits, even length.
+pub fn decode(input: &str) -> Result<Vec<u8>, HexError> {
+    decode_with(input, HexOptions::default())
+}
+
+pub fn decode_with(input: &str, opts: HexOptions) -> Result<Vec<u8>, HexError> {
+    use stream::Decode;
+
+    let mut output = Vec::with_capacity(input.len() / 2);
+    let mut decoder = Decoder::new(opts);
+
+    decoder
+        .update(input.as_bytes(), &mut output)
+        .and_then(|_| decoder.finish(&mut output))
+        .map_err(|e| e.with_input_char(input))?;
+    Ok(output)
+}
+
+pub fn encode(bytes: &[u8]) -> String {
+    encode_with_case(bytes, Case::Lower)
+}
+
+pub fn encode_with_case(bytes: &[u8], case: Case) -> String {
+    let mut hex_str = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        let [hi, lo] = encode_byte(b, case);
+        hex_str.push(hi as char);
+        hex_str.push(lo as char);
+    }
+    hex_str
+}
+
+fn encode_byte(b: u8, case: Case) -> [u8; 2] {
+    let table: &[u8; 16] = match case {
+        Case::Lower => b"0123456789abcdef",
+        Case::Upper => b"0123456789ABCDEF",
+    };
+    [table[(b >> 4) as usize], table[(b & 0x0f) as usize]]
+}
+
+/// Encoder behind [`HexWriter`](stream::HexWriter).
+pub struct Encoder {
+    case: Case,
+}
+
+impl Encoder {
+    pub fn new(case: Case) -> Self {
+        Encoder { case }
+    }
+}
+
+impl stream::Encode for Encoder {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        output.reserve(input.len() * 2);
+        for &b in input {
+            output.extend_from_slice(&encode_byte(b, self.case));
+        }
+    }
+
+    fn finish(&mut self, _output: &mut Vec<u8>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::hex::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let hex = "00ff10a5";
+        assert_eq!(decode(hex), Ok(vec![0x00, 0xff, 0x10, 0xa5]));
+        assert_eq!(encode(&decode(hex).unwrap()), hex);
+        assert_eq!(encode_with_case(&[0xab, 0xcd], Case::Upper), "ABCD");
+        assert_eq!(decode("ABcd"), Ok(vec![0xab, 0xcd]));
+    }
+
+    #[test]
+    fn test_hex_errors() {
+        assert_eq!(decode("abc"), Err(HexError::OddLength(3)));
+        assert_eq!(
+            decode("00zz"),
+            Err(HexError::InvalidChar { c: 'z', offset: 2 })
+        );
+        assert_eq!(
+            decode("00é0"),
+            Err(HexError::InvalidChar { c: 'é', offset: 2 })
+        );
+        assert_eq!(
+            decode("00 11"),
+            Err(HexError::InvalidChar { c: ' ', offset: 2 })
+        );
+        assert_eq!(
+            decode("0x11"),
+            Err(HexError::InvalidChar { c: 'x', offset: 1 })
+        );
+    }
+
+    #[test]
+    fn test_hex_tolerant() {
+        let opts = HexOptions::tolerant();
+        assert_eq!(
+            decode_with("0xdead\nBEEF\n", opts),
+            Ok(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(decode_with("0x01 0x02\t0X03", opts), Ok(vec![1, 2, 3]));
+        assert_eq!(decode_with("  \n", opts), Ok(vec![]));
+        // A prefix is only recognised at the start of a token.
+        assert_eq!(
+            decode_with("010x02", opts),
+            Err(HexError::InvalidChar { c: 'x', offset: 3 })
+        );
+        assert_eq!(decode_with("0x1 2 3", opts), Err(HexError::OddLength(3)));
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    error::Error,
+    io::{self, Read, Write},
+};
+
+use crate::encoding::{ascii85, base32, base58, base64, hex};
+
+/// Bytes pulled from the inner reader, or accepted by a writer, per step.
+/// Keeps every internal buffer bounded regardless of the stream length.
+const CHUNK_LEN: usize = 3 * 1024;
+
+/// An incremental decoder: text in, bytes out.
+pub trait Decode {
+    type Error: Error + Send + Sync + 'static;
+
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Flushes the final partial group and validates the end of the input.
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), Self::Error>;
+}
+
+/// An incremental encoder: bytes in, text out.
+pub trait Encode {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>);
+
+    /// Writes the final partial group, with padding where the codec has any.
+    fn finish(&mut self, output: &mut Vec<u8>);
+}
+
+/// Decodes text read from `R`. Malformed input surfaces as an
+/// [`io::ErrorKind::InvalidData`] error wrapping the decoder's error.
+pub struct DecodeReader<R, D> {
+    inner: R,
+    decoder: D,
+    out: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read, D: Decode> DecodeReader<R, D> {
+    pub fn from_decoder(inner: R, decoder: D) -> Self {
+        DecodeReader {
+            inner,
+            decoder,
+            out: Vec::with_capacity(CHUNK_LEN),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, D: Decode> Read for DecodeReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.out.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.out.clear();
+            self.pos = 0;
+
+            let mut chunk = [0u8; CHUNK_LEN];
+            let n = self.inner.read(&mut chunk)?;
+            let result = if n == 0 {
+                self.done = true;
+                self.decoder.finish(&mut self.out)
+            } else {
+                self.decoder.update(&chunk[..n], &mut self.out)
+            };
+            result.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+
+        let n = buf.len().min(self.out.len() - self.pos);
+        buf[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Encodes bytes written to it as text on `W`. The final partial group is
+/// written by [`EncodeWriter::finish`]; dropping the writer unfinished
+/// writes it too, but ignores any error, as `BufWriter` does.
+pub struct EncodeWriter<W: Write, E: Encode> {
+    /// `None` once finished.
+    inner: Option<W>,
+    encoder: E,
+    out: Vec<u8>,
+}
+
+impl<W: Write, E: Encode> EncodeWriter<W, E> {
+    pub fn from_encoder(inner: W, encoder: E) -> Self {
+        EncodeWriter {
+            inner: Some(inner),
+            encoder,
+            out: Vec::new(),
+        }
+    }
+
+    /// Writes the trailing group and hands back the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut inner = self.inner.take().expect("finished only once");
+        self.out.clear();
+        self.encoder.finish(&mut self.out);
+        inner.write_all(&self.out)?;
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+impl<W: Write, E: Encode> Write for EncodeWriter<W, E> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(CHUNK_LEN);
+        self.out.clear();
+        self.encoder.update(&buf[..n], &mut self.out);
+        self.inner
+            .as_mut()
+            .expect("not yet finished")
+            .write_all(&self.out)?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().expect("not yet finished").flush()
+    }
+}
+
+impl<W: Write, E: Encode> Drop for EncodeWriter<W, E> {
+    fn drop(&mut self) {
+        if let Some(mut inner) = self.inner.take() {
+            self.out.clear();
+            self.encoder.finish(&mut self.out);
+            let _ = inner.write_all(&self.out).and_then(|_| inner.flush());
+        }
+    }
+}
+
+pub type HexReader<R> = DecodeReader<R, hex::Decoder>;
+pub type HexWriter<W> = EncodeWriter<W, hex::Encoder>;
+pub type Base32Reader<R> = DecodeReader<R, base32::Decoder>;
+pub type Base32Writer<W> = EncodeWriter<W, base32::Encoder>;
+/// Base58 is a single big number, so this buffers the whole input until EOF.
+pub type Base58Reader<R> = DecodeReader<R, base58::Decoder>;
+/// Base58 is a single big number, so this buffers everything until `finish`
+/// or drop.
+pub type Base58Writer<W> = EncodeWriter<W, base58::Encoder>;
+pub type Base64Reader<R> = DecodeReader<R, base64::Decoder>;
+pub type Base64Writer<W> = EncodeWriter<W, base64::Encoder>;
+pub type Ascii85Reader<R> = DecodeReader<R, ascii85::Decoder>;
+pub type Ascii85Writer<W> = EncodeWriter<W, ascii85::Encoder>;
+
+impl<R: Read> HexReader<R> {
+    pub fn new(inner: R, opts: hex::HexOptions) -> Self {
+        Self::from_decoder(inner, hex::Decoder::new(opts))
+    }
+}
+
+impl<W: Write> HexWriter<W> {
+    pub fn new(inner: W, case: hex::Case) -> Self {
+        Self::from_encoder(inner, hex::Encoder::new(case))
+    }
+}
+
+impl<R: Read> Base32Reader<R> {
+    pub fn new(inner: R, config: base32::Config) -> Self {
+        Self::from_decoder(inner, base32::Decoder::new(config))
+    }
+}
+
+impl<W: Write> Base32Writer<W> {
+    pub fn new(inner: W, config: base32::Config) -> Self {
+        Self::from_encoder(inner, base32::Encoder::new(config))
+    }
+}
+
+impl<R: Read> Base58Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::from_decoder(inner, base58::Decoder::new())
+    }
+}
+
+impl<W: Write> Base58Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self::from_encoder(inner, base58::Encoder::new())
+    }
+}
+
+impl<R: Read> Base64Reader<R> {
+    pub fn new(inner: R, config: base64::Config) -> Self {
+        Self::from_decoder(inner, base64::Decoder::new(config))
+    }
+}
+
+impl<W: Write> Base64Writer<W> {
+    pub fn new(inner: W, config: base64::Config) -> Self {
+        Self::from_encoder(inner, base64::Encoder::new(config))
+    }
+}
+
+impl<R: Read> Ascii85Reader<R> {
+    pub fn new(inner: R, variant: ascii85::Variant) -> Self {
+        Self::from_decoder(inner, ascii85::Decoder::new(variant))
+    }
+}
+
+impl<W: Write> Ascii85Writer<W> {
+    pub fn new(inner: W, variant: ascii85::Variant) -> Self {
+        Self::from_encoder(inner, ascii85::Encoder::new(variant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use crate::encoding::{
+        DecodeError, ascii85, base32, base58,
+        base64::{self, Config},
+        hex::{self, Case, HexOptions},
+        stream::*,
+    };
+
+    /// Hands out at most `step` bytes per read to exercise chunk boundaries.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.step.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + 7) as u8).collect()
+    }
+
+    #[test]
+    fn test_base64_reader() {
+        let data = sample(10_000);
+        let encoded = base64::encode_with(&data, Config::MIME);
+
+        for step in [1, 3, 7, 4096] {
+            let mut reader = Base64Reader::new(
+                Trickle {
+                    data: encoded.as_bytes(),
+                    step,
+                },
+                Config::MIME,
+            );
+            let mut decoded = Vec::new();
+            reader.read_to_end(&mut decoded).unwrap();
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[test]
+    fn test_base64_reader_error() {
+        let mut reader = Base64Reader::new("Zm9v!A==".as_bytes(), Config::STANDARD);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidChar { c: '!', offset: 4 })
+        );
+
+        let mut reader = Base64Reader::new("Zm9vY".as_bytes(), Config::STANDARD);
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_base64_writer() {
+        let data = sample(10_001);
+        for config in [Config::STANDARD, Config::URL_SAFE_NO_PAD, Config::MIME] {
+            let mut writer = Base64Writer::new(Vec::new(), config);
+            for piece in data.chunks(5) {
+                writer.write_all(piece).unwrap();
+            }
+            let encoded = writer.finish().unwrap();
+            assert_eq!(
+                String::from_utf8(encoded).unwrap(),
+                base64::encode_with(&data, config)
+            );
+        }
+    }
+
+    #[test]
+    fn test_writer_drop_writes_tail() {
+        let mut encoded = Vec::new();
+        {
+            let mut writer = Base64Writer::new(&mut encoded, Config::STANDARD);
+            writer.write_all(b"hello").unwrap();
+        }
+        assert_eq!(encoded, b"aGVsbG8=");
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let data = sample(5_000);
+
+        let mut writer = HexWriter::new(Vec::new(), Case::Upper);
+        writer.write_all(&data).unwrap();
+        let encoded = writer.finish().unwrap();
+        assert_eq!(
+            encoded,
+            hex::encode_with_case(&data, Case::Upper).as_bytes()
+        );
+
+        let mut reader = HexReader::new(
+            Trickle {
+                data: &encoded,
+                step: 5,
+            },
+            HexOptions::default(),
+        );
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_hex_reader_prefix_across_reads() {
+        let input = b"0x de ad 0X be ef\n";
+        let mut reader = HexReader::new(
+            Trickle {
+                data: input,
+                step: 1,
+            },
+            HexOptions::tolerant(),
+        );
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, [0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_other_codecs_round_trip() {
+        let data = sample(2_003);
+
+        let mut writer = Base32Writer::new(Vec::new(), base32::Config::STANDARD);
+        data.chunks(7)
+            .for_each(|piece| writer.write_all(piece).unwrap());
+        let encoded = writer.finish().unwrap();
+        assert_eq!(encoded, base32::encode(&data).as_bytes());
+        let mut reader = Base32Reader::new(
+            Trickle {
+                data: &encoded,
+                step: 3,
+            },
+            base32::Config::STANDARD,
+        );
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+
+        let mut writer = Base58Writer::new(Vec::new());
+        data[..300]
+            .chunks(7)
+            .for_each(|piece| writer.write_all(piece).unwrap());
+        let encoded = writer.finish().unwrap();
+        assert_eq!(encoded, base58::encode(&data[..300]).as_bytes());
+        let mut decoded = Vec::new();
+        Base58Reader::new(Trickle {
+            data: &encoded,
+            step: 3,
+        })
+        .read_to_end(&mut decoded)
+        .unwrap();
+        assert_eq!(decoded, &data[..300]);
+
+        for variant in [ascii85::Variant::Ascii85, ascii85::Variant::Z85] {
+            let mut writer = Ascii85Writer::new(Vec::new(), variant);
+            data.chunks(7)
+                .for_each(|piece| writer.write_all(piece).unwrap());
+            let encoded = writer.finish().unwrap();
+            assert_eq!(encoded, ascii85::encode_with(&data, variant).as_bytes());
+            let mut decoded = Vec::new();
+            Ascii85Reader::new(
+                Trickle {
+                    data: &encoded,
+                    step: 3,
+                },
+                variant,
+            )
+            .read_to_end(&mut decoded)
+            .unwrap();
+            assert_eq!(decoded, data);
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    aes::{self, Aes128},
+    xor::{Keystream, xor_into},
+};
+
+/// A keyed permutation on fixed-size blocks.
+pub trait BlockCipher {
+    const BLOCK_LEN: usize;
+
+    /// `block` is exactly [`BlockCipher::BLOCK_LEN`] bytes.
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// `block` is exactly [`BlockCipher::BLOCK_LEN`] bytes.
+    fn decrypt_block(&self, block: &mut [u8]);
+}
+
+impl BlockCipher for Aes128 {
+    const BLOCK_LEN: usize = aes::BLOCK_LEN;
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        Aes128::encrypt_block(self, block.try_into().expect("16-byte block"));
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        Aes128::decrypt_block(self, block.try_into().expect("16-byte block"));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeError {
+    /// ECB and CBC only take whole blocks; pad first.
+    Unaligned { len: usize, block_len: usize },
+    /// The IV or initial counter block is not one block long.
+    IvLength { len: usize, block_len: usize },
+    /// A CTR edit starts past the end of the ciphertext.
+    OutOfRange { offset: usize, len: usize },
+}
+
+impl fmt::Display for ModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModeError::Unaligned { len, block_len } => {
+                write!(
+                    f,
+                    "{len} bytes is not a multiple of the {block_len}-byte block"
+                )
+            }
+            ModeError::IvLength { len, block_len } => {
+                write!(f, "IV of {len} bytes for a {block_len}-byte block")
+            }
+            ModeError::OutOfRange { offset, len } => {
+                write!(f, "offset {offset} is past the end of {len} bytes")
+            }
+        }
+    }
+}
+
+impl Error for ModeError {}
+
+fn check_aligned<C: BlockCipher>(buf: &[u8]) -> Result<(), ModeError> {
+    if !buf.len().is_multiple_of(C::BLOCK_LEN) {
+        return Err(ModeError::Unaligned {
+            len: buf.len(),
+            block_len: C::BLOCK_LEN,
+        });
+    }
+    Ok(())
+}
+
+fn check_iv<C: BlockCipher>(iv: &[u8]) -> Result<(), ModeError> {
+    if iv.len() != C::BLOCK_LEN {
+        return Err(ModeError::IvLength {
+            len: iv.len(),
+            block_len: C::BLOCK_LEN,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct Ecb<C> {
+    cipher: C,
+}
+
+impl<C: BlockCipher> Ecb<C> {
+    pub fn new(cipher: C) -> Self {
+        Ecb { cipher }
+    }
+
+    pub fn encrypt(&self, buf: &mut [u8]) -> Result<(), ModeError> {
+        check_aligned::<C>(buf)?;
+        for block in buf.chunks_exact_mut(C::BLOCK_LEN) {
+            self.cipher.encrypt_block(block);
+        }
+        Ok(())
+    }
+
+    pub fn decrypt(&self, buf: &mut [u8]) -> Result<(), ModeError> {
+        check_aligned::<C>(buf)?;
+        for block in buf.chunks_exact_mut(C::BLOCK_LEN) {
+            self.cipher.decrypt_block(block);
+        }
+        Ok(())
+    }
+}
+
+/// CBC with a caller-chosen IV. Each call starts again from that IV.
+#[derive(Debug, Clone)]
+pub struct Cbc<C> {
+    cipher: C,
+    iv: Vec<u8>,
+}
+
+impl<C: BlockCipher> Cbc<C> {
+    pub fn new(cipher: C, iv: &[u8]) -> Result<Self, ModeError> {
+        check_iv::<C>(iv)?;
+        Ok(Cbc {
+            cipher,
+            iv: iv.to_vec(),
+        })
+    }
+
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    pub fn encrypt(&self, buf: &mut [u8]) -> Result<(), ModeError> {
+        check_aligned::<C>(buf)?;
+        let mut prev = self.iv.clone();
+        for block in buf.chunks_exact_mut(C::BLOCK_LEN) {
+            xor_into(block, &prev).expect("block-sized");
+            self.cipher.encrypt_block(block);
+            prev.copy_from_slice(block);
+        }
+        Ok(())
+    }
+
+    pub fn decrypt(&self, buf: &mut [u8]) -> Result<(), ModeError> {
+        check_aligned::<C>(buf)?;
+        let mut prev = self.iv.clone();
+        let mut saved = vec![0u8; C::BLOCK_LEN];
+        for block in buf.chunks_exact_mut(C::BLOCK_LEN) {
+            saved.copy_from_slice(block);
+            self.cipher.decrypt_block(block);
+            xor_into(block, &prev).expect("block-sized");
+            std::mem::swap(&mut prev, &mut saved);
+        }
+        Ok(())
+    }
+}
+
+/// Where the block counter lives in a CTR counter block, and how it counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CounterLayout {
+    /// A nonce in the first half, then a little-endian 64-bit counter, as
+    /// the Cryptopals challenges use.
+    #[default]
+    LittleEndian64,
+    /// The last 16 bytes are one big-endian 128-bit counter, as in NIST
+    /// SP 800-38A and aws-lc-rs.
+    BigEndian128,
+}
+
+impl CounterLayout {
+    /// The counter block `blocks` steps after `initial`.
+    fn advance(self, initial: &[u8], blocks: u64, out: &mut [u8]) {
+        out.copy_from_slice(initial);
+        match self {
+            CounterLayout::LittleEndian64 => {
+                let ctr = &mut out[initial.len() - 8..];
+                let n = u64::from_le_bytes((&*ctr).try_into().unwrap()).wrapping_add(blocks);
+                ctr.copy_from_slice(&n.to_le_bytes());
+            }
+            CounterLayout::BigEndian128 => {
+                let ctr = &mut out[initial.len() - 16..];
+                let n =
+                    u128::from_be_bytes((&*ctr).try_into().unwrap()).wrapping_add(blocks as u128);
+                ctr.copy_from_slice(&n.to_be_bytes());
+            }
+        }
+    }
+}
+
+/// CTR mode as a [`Keystream`]: encrypting and decrypting are the same
+/// operation, and successive calls continue the stream.
+#[derive(Debug, Clone)]
+pub struct Ctr<C> {
+    cipher: C,
+    layout: CounterLayout,
+    initial: Vec<u8>,
+    /// Keystream bytes used so far.
+    pos: u64,
+    block: Vec<u8>,
+}
+
+impl<C: BlockCipher> Ctr<C> {
+    /// Starts from the given counter block, which holds the nonce and the
+    /// initial counter in `layout`'s format.
+    pub fn new(cipher: C, initial: &[u8], layout: CounterLayout) -> Result<Self, ModeError> {
+        check_iv::<C>(initial)?;
+        let min_len = match layout {
+            CounterLayout::LittleEndian64 => 8,
+            CounterLayout::BigEndian128 => 16,
+        };
+        assert!(C::BLOCK_LEN >= min_len, "block too short for {layout:?}");
+
+        Ok(Ctr {
+            cipher,
+            layout,
+            initial: initial.to_vec(),
+            pos: 0,
+            block: vec![0; C::BLOCK_LEN],
+        })
+    }
+
+    /// A little-endian nonce, then a little-endian counter from zero.
+    pub fn with_nonce(cipher: C, nonce: u64) -> Self {
+        let mut initial = vec![0; C::BLOCK_LEN];
+        initial[..8].copy_from_slice(&nonce.to_le_bytes());
+        Ctr::new(cipher, &initial, CounterLayout::LittleEndian64).expect("block-sized IV")
+    }
+
+    /// Keystream bytes used so far.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Moves to keystream byte `pos`, so the next byte applied is the one
+    /// at that offset in the message.
+    pub fn seek(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// `ciphertext` with the plaintext from `offset` onwards replaced by
+    /// `newtext`, growing it if `newtext` runs past the end. Leaves this
+    /// keystream where it was.
+    pub fn edit(
+        &self,
+        ciphertext: &[u8],
+        offset: usize,
+        newtext: &[u8],
+    ) -> Result<Vec<u8>, ModeError>
+    where
+        C: Clone,
+    {
+        if offset > ciphertext.len() {
+            return Err(ModeError::OutOfRange {
+                offset,
+                len: ciphertext.len(),
+            });
+        }
+
+        let mut patch = newtext.to_vec();
+        let mut ctr = self.clone();
+        ctr.seek(offset as u64);
+        ctr.apply(&mut patch);
+
+        let mut out = ciphertext.to_vec();
+        let end = (offset + patch.len()).min(out.len());
+        out.splice(offset..end, patch);
+        Ok(out)
+    }
+
+    fn keystream_block(&mut self, index: u64) {
+        self.layout.advance(&self.initial, index, &mut self.block);
+        self.cipher.encrypt_block(&mut self.block);
+    }
+}
+
+impl<C: BlockCipher> Keystream for Ctr<C> {
+    fn apply(&mut self, mut buf: &mut [u8]) {
+        let block_len = C::BLOCK_LEN as u64;
+        while !buf.is_empty() {
+            let offset = (self.pos % block_len) as usize;
+            self.keystream_block(self.pos / block_len);
+            let n = buf.len().min(C::BLOCK_LEN - offset);
+            let (head, rest) = buf.split_at_mut(n);
+            xor_into(head, &self.block[offset..offset + n]).expect("equal lengths");
+            self.pos += n as u64;
+            buf = rest;
+        }
+    }
+}
+
+/// Challenge 25's random-access CTR: AES-128 with a little-endian nonce
+/// and counter, seekable to any byte of the message.
+#[derive(Debug, Clone)]
+pub struct AesCtr(Ctr<Aes128>);
+
+impl AesCtr {
+    pub fn new(key: &[u8; 16], nonce: u64) -> Self {
+        AesCtr(Ctr::with_nonce(Aes128::new(key), nonce))
+    }
+
+    pub fn position(&self) -> u64 {
+        self.0.position()
+    }
+
+    pub fn seek(&mut self, pos: u64) {
+        self.0.seek(pos);
+    }
+
+    pub fn edit(
+        &self,
+        ciphertext: &[u8],
+        offset: usize,
+        newtext: &[u8],
+    ) -> Result<Vec<u8>, ModeError> {
+        self.0.edit(ciphertext, offset, newtext)
+    }
+
+    /// Encrypts what is written to `inner` and decrypts what is read from
+    /// it.
+    pub fn stream<S>(self, inner: S) -> CtrStream<S, Aes128> {
+        CtrStream::new(inner, self.0)
+    }
+}
+
+impl Keystream for AesCtr {
+    fn apply(&mut self, buf: &mut [u8]) {
+        self.0.apply(buf);
+    }
+}
+
+/// CTR over an I/O stream: reads are decrypted, writes are encrypted, and
+/// seeking moves the keystream along with the stream. The stream's byte 0
+/// is keystream byte 0.
+#[derive(Debug, Clone)]
+pub struct CtrStream<S, C> {
+    inner: S,
+    ctr: Ctr<C>,
+}
+
+impl<S, C: BlockCipher> CtrStream<S, C> {
+    /// `ctr` should be positioned where `inner` is.
+    pub fn new(inner: S, ctr: Ctr<C>) -> Self {
+        CtrStream { inner, ctr }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Read, C: BlockCipher> Read for CtrStream<S, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.ctr.apply(&mut buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<S: Write, C: BlockCipher> Write for CtrStream<S, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = self.ctr.position();
+        let mut encrypted = buf.to_vec();
+        self.ctr.apply(&mut encrypted);
+        // A short write must only use up the keystream it consumed.
+        let n = self.inner.write(&encrypted);
+        self.ctr.seek(start + *n.as_ref().unwrap_or(&0) as u64);
+        n
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<S: Seek, C: BlockCipher> Seek for CtrStream<S, C> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = self.inner.seek(pos)?;
+        self.ctr.seek(pos);
+        Ok(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_lc_rs::cipher::{
+        AES_128, EncryptingKey, EncryptionContext, PaddedBlockEncryptingKey, UnboundCipherKey,
+    };
+
+    use crate::{aes::Aes128, encoding::base64_decode, modes::*, xor::apply_keystream};
+
+    const KEY: &[u8; 16] = b"YELLOW SUBMARINE";
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + 7) as u8).collect()
+    }
+
+    fn unbound() -> UnboundCipherKey {
+        UnboundCipherKey::new(&AES_128, KEY).unwrap()
+    }
+
+    #[test]
+    fn test_ecb_matches_aws_lc() {
+        let data = sample(20 * 16);
+        let mut expected = data.clone();
+        EncryptingKey::ecb(unbound())
+            .unwrap()
+            .encrypt(&mut expected)
+            .unwrap();
+
+        let ecb = Ecb::new(Aes128::new(KEY));
+        let mut buf = data.clone();
+        ecb.encrypt(&mut buf).unwrap();
+        assert_eq!(buf, expected);
+        ecb.decrypt(&mut buf).unwrap();
+        assert_eq!(buf, data);
+
+        assert_eq!(
+            ecb.encrypt(&mut [0; 17]),
+            Err(ModeError::Unaligned {
+                len: 17,
+                block_len: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_cbc_matches_aws_lc() {
+        let iv = [0x42u8; 16];
+        let data = sample(20 * 16);
+
+        // aws-lc-rs only offers CBC with PKCS#7, which appends one whole
+        // padding block to aligned input.
+        let mut expected = data.clone();
+        PaddedBlockEncryptingKey::cbc_pkcs7(unbound())
+            .unwrap()
+            .less_safe_encrypt(&mut expected, EncryptionContext::Iv128(iv.into()))
+            .unwrap();
+
+        let cbc = Cbc::new(Aes128::new(KEY), &iv).unwrap();
+        let mut buf = data.clone();
+        cbc.encrypt(&mut buf).unwrap();
+        assert_eq!(buf, expected[..data.len()]);
+        cbc.decrypt(&mut buf).unwrap();
+        assert_eq!(buf, data);
+
+        assert_eq!(
+            Cbc::new(Aes128::new(KEY), &iv[..8]).err(),
+            Some(ModeError::IvLength {
+                len: 8,
+                block_len: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_ctr_matches_aws_lc() {
+        // Start near the top of the low 64 bits so the carry is exercised.
+        let mut iv = [0xffu8; 16];
+        iv[..8].copy_from_slice(b"nonce!!!");
+        iv[15] = 0xfd;
+        let data = sample(100);
+
+        let mut expected = data.clone();
+        EncryptingKey::ctr(unbound())
+            .unwrap()
+            .less_safe_encrypt(&mut expected, EncryptionContext::Iv128(iv.into()))
+            .unwrap();
+
+        let mut ctr = Ctr::new(Aes128::new(KEY), &iv, CounterLayout::BigEndian128).unwrap();
+        let mut buf = data.clone();
+        for piece in buf.chunks_mut(7) {
+            ctr.apply(piece);
+        }
+        assert_eq!(buf, expected);
+        assert_eq!(ctr.position(), 100);
+    }
+
+    #[test]
+    fn test_ctr_c18() {
+        let mut buf = base64_decode(
+            "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==",
+        )
+        .unwrap();
+
+        apply_keystream(&mut buf, Ctr::with_nonce(Aes128::new(KEY), 0));
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby "
+        );
+    }
+
+    #[test]
+    fn test_ctr_seek_and_edit() {
+        let data = sample(100);
+        let mut ciphertext = data.clone();
+        apply_keystream(&mut ciphertext, AesCtr::new(KEY, 7));
+
+        // Decrypting from the middle matches the same slice of the whole.
+        let mut ctr = AesCtr::new(KEY, 7);
+        for offset in [0, 5, 16, 33, 99] {
+            ctr.seek(offset as u64);
+            let mut tail = ciphertext[offset..].to_vec();
+            ctr.apply(&mut tail);
+            assert_eq!(tail, data[offset..]);
+        }
+
+        let edited = ctr.edit(&ciphertext, 20, b"hello").unwrap();
+        let mut plaintext = edited.clone();
+        apply_keystream(&mut plaintext, AesCtr::new(KEY, 7));
+        assert_eq!(&plaintext[20..25], b"hello");
+        assert_eq!(plaintext[..20], data[..20]);
+        assert_eq!(plaintext[25..], data[25..]);
+        assert_eq!(ctr.position(), 100);
+
+        let grown = ctr.edit(&ciphertext, 98, b"overflow").unwrap();
+        assert_eq!(grown.len(), 106);
+        assert_eq!(
+            ctr.edit(&ciphertext, 101, b"x"),
+            Err(ModeError::OutOfRange {
+                offset: 101,
+                len: 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_ctr_stream() {
+        let data = sample(100);
+        let mut expected = data.clone();
+        apply_keystream(&mut expected, AesCtr::new(KEY, 3));
+
+        let mut writer = AesCtr::new(KEY, 3).stream(io::Cursor::new(Vec::new()));
+        for piece in data.chunks(9) {
+            writer.write_all(piece).unwrap();
+        }
+        assert_eq!(writer.get_ref().get_ref(), &expected);
+
+        let mut reader = AesCtr::new(KEY, 3).stream(writer.into_inner());
+        reader.seek(SeekFrom::Start(40)).unwrap();
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, data[40..]);
+
+        reader.seek(SeekFrom::Current(-70)).unwrap();
+        reader.write_all(b"patched").unwrap();
+        reader.seek(SeekFrom::Start(30)).unwrap();
+        let mut patched = [0u8; 7];
+        reader.read_exact(&mut patched).unwrap();
+        assert_eq!(&patched, b"patched");
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    aes::{self, Aes128},
+    analysis::detect_ecb,
+    modes::{Cbc, Ecb},
+    padding::pkcs7_pad,
+    random::{random_array, random_bool, random_bytes, random_in},
+};
+
+/// Something that encrypts attacker-chosen input under a secret.
+pub trait Oracle {
+    fn query(&mut self, input: &[u8]) -> Vec<u8>;
+}
+
+impl<F: FnMut(&[u8]) -> Vec<u8>> Oracle for F {
+    fn query(&mut self, input: &[u8]) -> Vec<u8> {
+        self(input)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Ecb,
+    Cbc,
+}
+
+/// Challenge 11: every query picks a fresh key, ECB or CBC at random (CBC
+/// with a random IV), and wraps the input in 5 to 10 random bytes on each
+/// side before padding and encrypting.
+#[derive(Debug, Clone, Default)]
+pub struct EcbOrCbc {
+    last_mode: Option<Mode>,
+}
+
+impl EcbOrCbc {
+    pub fn new() -> Self {
+        EcbOrCbc::default()
+    }
+
+    /// The mode the last query used, for scoring guesses.
+    pub fn last_mode(&self) -> Option<Mode> {
+        self.last_mode
+    }
+}
+
+impl Oracle for EcbOrCbc {
+    fn query(&mut self, input: &[u8]) -> Vec<u8> {
+        let mut plaintext = random_bytes(random_in(5..=10));
+        plaintext.extend_from_slice(input);
+        plaintext.extend_from_slice(&random_bytes(random_in(5..=10)));
+        let mut buf = pkcs7_pad(&plaintext, aes::BLOCK_LEN);
+
+        let cipher = Aes128::new(&random_array());
+        let mode = if random_bool() { Mode::Ecb } else { Mode::Cbc };
+        match mode {
+            Mode::Ecb => Ecb::new(cipher).encrypt(&mut buf),
+            Mode::Cbc => Cbc::new(cipher, &random_array::<16>())
+                .expect("block-sized IV")
+                .encrypt(&mut buf),
+        }
+        .expect("padded input");
+        self.last_mode = Some(mode);
+        buf
+    }
+}
+
+/// Sends enough identical bytes that, whatever the prefix length, at least
+/// two whole blocks of them line up; only ECB makes those blocks repeat.
+pub fn guess_mode(oracle: &mut impl Oracle) -> Mode {
+    let input = [b'A'; 3 * aes::BLOCK_LEN];
+    if detect_ecb(&oracle.query(&input), aes::BLOCK_LEN).repeated_blocks > 0 {
+        Mode::Ecb
+    } else {
+        Mode::Cbc
+    }
+}
+
+/// The share of `trials` fresh queries whose mode [`guess_mode`] gets right.
+pub fn guess_mode_accuracy(oracle: &mut EcbOrCbc, trials: usize) -> f64 {
+    let correct = (0..trials)
+        .filter(|_| Some(guess_mode(oracle)) == oracle.last_mode())
+        .count();
+    correct as f64 / trials as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::oracle::*;
+
+    #[test]
+    fn test_c11() {
+        let mut oracle = EcbOrCbc::new();
+        assert_eq!(oracle.last_mode(), None);
+        assert_eq!(guess_mode_accuracy(&mut oracle, 200), 1.0);
+    }
+
+    #[test]
+    fn test_oracle_output() {
+        let mut oracle = EcbOrCbc::new();
+        for len in [0, 1, 15, 16, 40] {
+            let ciphertext = oracle.query(&vec![0; len]);
+            assert_eq!(ciphertext.len() % aes::BLOCK_LEN, 0);
+            assert!(ciphertext.len() > len + 10);
+            assert!(ciphertext.len() <= len + 20 + aes::BLOCK_LEN);
+        }
+
+        let mut closure = |input: &[u8]| input.to_vec();
+        assert_eq!(guess_mode(&mut closure), Mode::Ecb);
+    }
+}
@@ -0,0 +1,332 @@
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingError {
+    /// There is nothing to unpad.
+    Empty,
+    /// The final byte gives a pad length of zero or longer than the data.
+    BadLength(u8),
+    /// The pad bytes do not all repeat the length byte.
+    Inconsistent,
+    /// ISO/IEC 7816-4: no `0x80` marker before the trailing zeros.
+    MissingMarker,
+    /// The data is not a whole number of blocks.
+    Unaligned { len: usize, block_size: usize },
+}
+
+impl fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaddingError::Empty => write!(f, "no data to unpad"),
+            PaddingError::BadLength(n) => write!(f, "invalid pad length {n}"),
+            PaddingError::Inconsistent => write!(f, "inconsistent pad bytes"),
+            PaddingError::MissingMarker => write!(f, "missing 0x80 pad marker"),
+            PaddingError::Unaligned { len, block_size } => {
+                write!(f, "{len} bytes is not whole {block_size}-byte blocks")
+            }
+        }
+    }
+}
+
+impl Error for PaddingError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Padding {
+    /// `n` bytes of value `n`.
+    #[default]
+    Pkcs7,
+    /// `n - 1` zero bytes, then `n`.
+    AnsiX923,
+    /// `0x80`, then zeros to the block boundary.
+    Iso7816,
+}
+
+impl Padding {
+    /// Pads to a whole number of blocks, always adding at least one byte.
+    ///
+    /// # Panics
+    ///
+    /// As the scheme's pad function does for a bad `block_size`.
+    pub fn pad(self, data: &[u8], block_size: usize) -> Vec<u8> {
+        match self {
+            Padding::Pkcs7 => pkcs7_pad(data, block_size),
+            Padding::AnsiX923 => ansi_x923_pad(data, block_size),
+            Padding::Iso7816 => iso7816_pad(data, block_size),
+        }
+    }
+
+    pub fn unpad(self, data: &[u8]) -> Result<&[u8], PaddingError> {
+        match self {
+            Padding::Pkcs7 => pkcs7_unpad(data),
+            Padding::AnsiX923 => ansi_x923_unpad(data),
+            Padding::Iso7816 => iso7816_unpad(data),
+        }
+    }
+
+    /// Like [`unpad`](Self::unpad), but also requires whole blocks and a
+    /// pad that fits in the last one.
+    pub fn unpad_block(self, data: &[u8], block_size: usize) -> Result<&[u8], PaddingError> {
+        match self {
+            Padding::Pkcs7 => pkcs7_unpad_block(data, block_size),
+            Padding::AnsiX923 => ansi_x923_unpad_block(data, block_size),
+            Padding::Iso7816 => iso7816_unpad_block(data, block_size),
+        }
+    }
+}
+
+fn pad_len(len: usize, block_size: usize) -> u8 {
+    assert!(
+        (1..=255).contains(&block_size),
+        "block size {block_size} out of range"
+    );
+    (block_size - len % block_size) as u8
+}
+
+/// # Panics
+///
+/// If `block_size` is 0 or over 255, which the length byte cannot express.
+pub fn pkcs7_pad(data: &[u8], block_size: usize) -> Vec<u8> {
+    let n = pad_len(data.len(), block_size);
+    let mut out = Vec::with_capacity(data.len() + n as usize);
+    out.extend_from_slice(data);
+    out.resize(data.len() + n as usize, n);
+    out
+}
+
+/// Checks every byte a valid pad could cover, whatever the length byte says,
+/// so the time taken depends on the data length and not on where the pad
+/// goes wrong. The error kind itself still tells an oracle a great deal.
+///
+/// Without a block size any pad up to 255 bytes is accepted; use
+/// [`pkcs7_unpad_block`] when the block size is known.
+pub fn pkcs7_unpad(data: &[u8]) -> Result<&[u8], PaddingError> {
+    let n = pad_length_byte(data)?;
+
+    let window = data.len().min(255);
+    let mut diff = 0u8;
+    for (i, &b) in data[data.len() - window..].iter().rev().enumerate() {
+        let in_pad = ((i < n as usize) as u8).wrapping_neg();
+        diff |= (b ^ n) & in_pad;
+    }
+    if diff != 0 {
+        return Err(PaddingError::Inconsistent);
+    }
+    Ok(&data[..data.len() - n as usize])
+}
+
+/// PKCS#7 as a block cipher sees it: whole blocks only, and a pad no
+/// longer than one block.
+pub fn pkcs7_unpad_block(data: &[u8], block_size: usize) -> Result<&[u8], PaddingError> {
+    check_blocks(data, block_size)?;
+    check_pad_fits(data, block_size)?;
+    pkcs7_unpad(data)
+}
+
+/// # Panics
+///
+/// If `block_size` is 0 or over 255, which the length byte cannot express.
+pub fn ansi_x923_pad(data: &[u8], block_size: usize) -> Vec<u8> {
+    let n = pad_len(data.len(), block_size);
+    let mut out = Vec::with_capacity(data.len() + n as usize);
+    out.extend_from_slice(data);
+    out.resize(data.len() + n as usize - 1, 0);
+    out.push(n);
+    out
+}
+
+/// Only the length byte is checked: X9.23 allows any filler, though
+/// [`ansi_x923_pad`] writes zeros.
+pub fn ansi_x923_unpad(data: &[u8]) -> Result<&[u8], PaddingError> {
+    let n = pad_length_byte(data)?;
+    Ok(&data[..data.len() - n as usize])
+}
+
+pub fn ansi_x923_unpad_block(data: &[u8], block_size: usize) -> Result<&[u8], PaddingError> {
+    check_blocks(data, block_size)?;
+    check_pad_fits(data, block_size)?;
+    ansi_x923_unpad(data)
+}
+
+/// # Panics
+///
+/// If `block_size` is 0.
+pub fn iso7816_pad(data: &[u8], block_size: usize) -> Vec<u8> {
+    assert!(block_size > 0, "block size must be non-zero");
+    let n = block_size - data.len() % block_size;
+    let mut out = Vec::with_capacity(data.len() + n);
+    out.extend_from_slice(data);
+    out.push(0x80);
+    out.resize(data.len() + n, 0);
+    out
+}
+
+pub fn iso7816_unpad(data: &[u8]) -> Result<&[u8], PaddingError> {
+    if data.is_empty() {
+        return Err(PaddingError::Empty);
+    }
+    match data.iter().rposition(|&b| b != 0) {
+        Some(i) if data[i] == 0x80 => Ok(&data[..i]),
+        _ => Err(PaddingError::MissingMarker),
+    }
+}
+
+pub fn iso7816_unpad_block(data: &[u8], block_size: usize) -> Result<&[u8], PaddingError> {
+    check_blocks(data, block_size)?;
+    let unpadded = iso7816_unpad(data)?;
+    if unpadded.len() < data.len() - block_size {
+        return Err(PaddingError::MissingMarker);
+    }
+    Ok(unpadded)
+}
+
+fn check_blocks(data: &[u8], block_size: usize) -> Result<(), PaddingError> {
+    if data.is_empty() {
+        return Err(PaddingError::Empty);
+    }
+    if block_size == 0 || !data.len().is_multiple_of(block_size) {
+        return Err(PaddingError::Unaligned {
+            len: data.len(),
+            block_size,
+        });
+    }
+    Ok(())
+}
+
+fn check_pad_fits(data: &[u8], block_size: usize) -> Result<(), PaddingError> {
+    let n = pad_length_byte(data)?;
+    if n as usize > block_size {
+        return Err(PaddingError::BadLength(n));
+    }
+    Ok(())
+}
+
+fn pad_length_byte(data: &[u8]) -> Result<u8, PaddingError> {
+    let n = *data.last().ok_or(PaddingError::Empty)?;
+    if n == 0 || n as usize > data.len() {
+        return Err(PaddingError::BadLength(n));
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::padding::*;
+
+    #[test]
+    fn test_c9() {
+        assert_eq!(
+            pkcs7_pad(b"YELLOW SUBMARINE", 20),
+            b"YELLOW SUBMARINE\x04\x04\x04\x04"
+        );
+        assert_eq!(pkcs7_pad(b"", 4), b"\x04\x04\x04\x04");
+        assert_eq!(pkcs7_pad(b"abcd", 4), b"abcd\x04\x04\x04\x04");
+    }
+
+    #[test]
+    fn test_c15() {
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x04\x04\x04\x04"),
+            Ok(&b"ICE ICE BABY"[..])
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x05\x05\x05\x05"),
+            Err(PaddingError::Inconsistent)
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x01\x02\x03\x04"),
+            Err(PaddingError::Inconsistent)
+        );
+    }
+
+    #[test]
+    fn test_pkcs7_errors() {
+        assert_eq!(pkcs7_unpad(b""), Err(PaddingError::Empty));
+        assert_eq!(pkcs7_unpad(b"abc\x00"), Err(PaddingError::BadLength(0)));
+        assert_eq!(pkcs7_unpad(b"\x03\x03"), Err(PaddingError::BadLength(3)));
+        assert_eq!(pkcs7_unpad(b"\x02\x02"), Ok(&b""[..]));
+
+        let mut long = vec![b'x'; 300];
+        long.extend_from_slice(&[0xff; 255]);
+        assert_eq!(pkcs7_unpad(&long), Ok(&[b'x'; 300][..]));
+        long[300] = 0xfe;
+        assert_eq!(pkcs7_unpad(&long), Err(PaddingError::Inconsistent));
+    }
+
+    #[test]
+    fn test_unpad_block() {
+        assert_eq!(pkcs7_unpad(&[0x11; 17]), Ok(&[][..]));
+        assert_eq!(
+            pkcs7_unpad_block(&[0x11; 32], 16),
+            Err(PaddingError::BadLength(0x11))
+        );
+        assert_eq!(
+            pkcs7_unpad_block(&[0x11; 17], 16),
+            Err(PaddingError::Unaligned {
+                len: 17,
+                block_size: 16
+            })
+        );
+        assert_eq!(
+            pkcs7_unpad_block(b"ICE ICE BABY\x04\x04\x04\x04", 0),
+            Err(PaddingError::Unaligned {
+                len: 16,
+                block_size: 0
+            })
+        );
+        assert_eq!(pkcs7_unpad_block(b"", 16), Err(PaddingError::Empty));
+        assert_eq!(pkcs7_unpad_block(&[0x10; 16], 16), Ok(&[][..]));
+
+        let mut x923 = [0u8; 32];
+        x923[31] = 17;
+        assert_eq!(ansi_x923_unpad(&x923), Ok(&[0u8; 15][..]));
+        assert_eq!(
+            ansi_x923_unpad_block(&x923, 16),
+            Err(PaddingError::BadLength(17))
+        );
+
+        let mut iso = [0u8; 32];
+        iso[8] = 0x80;
+        assert_eq!(iso7816_unpad(&iso), Ok(&[0u8; 8][..]));
+        assert_eq!(
+            iso7816_unpad_block(&iso, 16),
+            Err(PaddingError::MissingMarker)
+        );
+    }
+
+    #[test]
+    fn test_schemes_round_trip() {
+        let data: Vec<u8> = (0..40).collect();
+        for padding in [Padding::Pkcs7, Padding::AnsiX923, Padding::Iso7816] {
+            for len in 0..=data.len() {
+                for block_size in [1, 8, 16] {
+                    let padded = padding.pad(&data[..len], block_size);
+                    assert_eq!(padded.len() % block_size, 0);
+                    assert!(padded.len() > len);
+                    assert_eq!(padding.unpad(&padded), Ok(&data[..len]), "{padding:?}");
+                    assert_eq!(
+                        padding.unpad_block(&padded, block_size),
+                        Ok(&data[..len]),
+                        "{padding:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_other_schemes() {
+        assert_eq!(ansi_x923_pad(b"abcde", 8), b"abcde\x00\x00\x03");
+        assert_eq!(ansi_x923_unpad(b"abcde\x17\x2a\x03"), Ok(&b"abcde"[..]));
+        assert_eq!(ansi_x923_unpad(b"ab\x09"), Err(PaddingError::BadLength(9)));
+
+        assert_eq!(iso7816_pad(b"abcde", 8), b"abcde\x80\x00\x00");
+        assert_eq!(iso7816_unpad(b"abcde\x80\x00\x00"), Ok(&b"abcde"[..]));
+        assert_eq!(iso7816_unpad(b"abcde\x80"), Ok(&b"abcde"[..]));
+        assert_eq!(iso7816_unpad(b"\x00\x00"), Err(PaddingError::MissingMarker));
+        assert_eq!(
+            iso7816_unpad(b"ab\x01\x00"),
+            Err(PaddingError::MissingMarker)
+        );
+        assert_eq!(iso7816_unpad(b""), Err(PaddingError::Empty));
+    }
+}
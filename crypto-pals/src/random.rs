@@ -0,0 +1,62 @@
+use std::ops::RangeInclusive;
+
+use aws_lc_rs::rand;
+
+/// Bytes from the system's secure generator.
+pub fn random_bytes(len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    rand::fill(&mut buf).expect("system randomness");
+    buf
+}
+
+pub fn random_array<const N: usize>() -> [u8; N] {
+    let mut buf = [0; N];
+    rand::fill(&mut buf).expect("system randomness");
+    buf
+}
+
+pub fn random_bool() -> bool {
+    random_array::<1>()[0] & 1 == 1
+}
+
+/// A value in `range`, by rejection sampling so every value is equally
+/// likely.
+pub fn random_in(range: RangeInclusive<usize>) -> usize {
+    let (low, high) = range.into_inner();
+    assert!(low <= high, "empty range");
+    // Only the full 64-bit range overflows, and it needs no rejection.
+    let Some(span) = ((high - low) as u64).checked_add(1) else {
+        return usize::from_le_bytes(random_array());
+    };
+    let zone = u64::MAX - u64::MAX % span;
+    loop {
+        let n = u64::from_le_bytes(random_array());
+        if n < zone {
+            return low + (n % span) as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::*;
+
+    #[test]
+    fn test_random_in() {
+        let mut seen = [false; 6];
+        for _ in 0..500 {
+            let n = random_in(5..=10);
+            assert!((5..=10).contains(&n));
+            seen[n - 5] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        assert_eq!(random_in(3..=3), 3);
+        assert_eq!(random_in(usize::MAX..=usize::MAX), usize::MAX);
+
+        // Full-width ranges must not overflow computing their span.
+        let draws: Vec<_> = (0..8).map(|_| random_in(0..=usize::MAX)).collect();
+        assert!(draws.iter().any(|&n| n != draws[0]));
+        random_in(1..=usize::MAX);
+        assert_ne!(random_bytes(32), random_bytes(32));
+    }
+}
@@ -0,0 +1,283 @@
+use std::{error::Error, fmt};
+
+const WORD: usize = size_of::<u64>();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XorError {
+    /// Fixed XOR needs both inputs to be the same length.
+    LengthMismatch { left: usize, right: usize },
+    /// A repeating key must have at least one byte.
+    EmptyKey,
+}
+
+impl fmt::Display for XorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XorError::LengthMismatch { left, right } => {
+                write!(f, "length mismatch: {left} != {right}")
+            }
+            XorError::EmptyKey => write!(f, "empty key"),
+        }
+    }
+}
+
+impl Error for XorError {}
+
+pub(crate) fn check_lengths(b1: &[u8], b2: &[u8]) -> Result<(), XorError> {
+    if b1.len() != b2.len() {
+        return Err(XorError::LengthMismatch {
+            left: b1.len(),
+            right: b2.len(),
+        });
+    }
+    Ok(())
+}
+
+pub fn fixed_xor(b1: &[u8], b2: &[u8]) -> Result<Vec<u8>, XorError> {
+    let mut out = b1.to_vec();
+    xor_into(&mut out, b2)?;
+    Ok(out)
+}
+
+/// `dst ^= src`, a `u64` at a time.
+pub fn xor_into(dst: &mut [u8], src: &[u8]) -> Result<(), XorError> {
+    check_lengths(dst, src)?;
+
+    for (d, s) in dst.chunks_mut(WORD).zip(words(src)) {
+        let word = load_word(d) ^ s;
+        d.copy_from_slice(&word.to_ne_bytes()[..d.len()]);
+    }
+    Ok(())
+}
+
+/// `bytes` as native-endian `u64`s, the last one zero-extended if short.
+pub(crate) fn words(bytes: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    bytes.chunks(WORD).map(load_word)
+}
+
+fn load_word(chunk: &[u8]) -> u64 {
+    let mut word = [0u8; WORD];
+    word[..chunk.len()].copy_from_slice(chunk);
+    u64::from_ne_bytes(word)
+}
+
+/// A source of key bytes to XOR into data. Applying it twice with a fresh
+/// keystream undoes it; applying it in pieces continues where the last
+/// piece stopped.
+pub trait Keystream {
+    /// XORs the next `buf.len()` keystream bytes into `buf`.
+    fn apply(&mut self, buf: &mut [u8]);
+}
+
+/// The same byte over and over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SingleByte(pub u8);
+
+impl Keystream for SingleByte {
+    fn apply(&mut self, buf: &mut [u8]) {
+        xor_assign(buf, &[self.0]).expect("non-empty key");
+    }
+}
+
+/// A key repeated end to end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatingKey<'a> {
+    key: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RepeatingKey<'a> {
+    pub fn new(key: &'a [u8]) -> Result<Self, XorError> {
+        if key.is_empty() {
+            return Err(XorError::EmptyKey);
+        }
+        Ok(RepeatingKey { key, pos: 0 })
+    }
+}
+
+impl Keystream for RepeatingKey<'_> {
+    fn apply(&mut self, buf: &mut [u8]) {
+        // Finish the key's current cycle, then the rest starts aligned.
+        let head = buf.len().min((self.key.len() - self.pos) % self.key.len());
+        let (first, rest) = buf.split_at_mut(head);
+        for (b, k) in first.iter_mut().zip(&self.key[self.pos..]) {
+            *b ^= k;
+        }
+        xor_assign(rest, self.key).expect("non-empty key");
+        self.pos = (self.pos + buf.len()) % self.key.len();
+    }
+}
+
+/// Any byte iterator, such as a stream cipher's output. Bytes past the end
+/// of a finite iterator are left unchanged.
+impl<I: Iterator<Item = u8>> Keystream for I {
+    fn apply(&mut self, buf: &mut [u8]) {
+        for (b, k) in buf.iter_mut().zip(self) {
+            *b ^= k;
+        }
+    }
+}
+
+/// XORs `keystream` into `buf` in place. Pass `iter.by_ref()` to keep using
+/// an iterator afterwards.
+pub fn apply_keystream(buf: &mut [u8], mut keystream: impl Keystream) {
+    keystream.apply(buf);
+}
+
+pub fn xor_with_key(bytes: &[u8], key: u8) -> Vec<u8> {
+    let mut out = bytes.to_vec();
+    apply_keystream(&mut out, SingleByte(key));
+    out
+}
+
+pub fn rolling_xor(bytes: &[u8], key: &[u8]) -> Result<Vec<u8>, XorError> {
+    let mut out = bytes.to_vec();
+    apply_keystream(&mut out, RepeatingKey::new(key)?);
+    Ok(out)
+}
+
+/// `buf ^= key` with `key` repeated over the whole buffer.
+pub fn xor_assign(buf: &mut [u8], key: &[u8]) -> Result<(), XorError> {
+    if key.is_empty() {
+        return Err(XorError::EmptyKey);
+    }
+
+    // Tile short keys so the word-at-a-time path gets long runs.
+    let mut tiled = [0u8; 64];
+    let key = if key.len() < tiled.len() {
+        let reps = tiled.len() / key.len();
+        for chunk in tiled.chunks_exact_mut(key.len()).take(reps) {
+            chunk.copy_from_slice(key);
+        }
+        &tiled[..reps * key.len()]
+    } else {
+        key
+    };
+
+    for chunk in buf.chunks_mut(key.len()) {
+        let len = chunk.len();
+        xor_into(chunk, &key[..len])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{bytes_to_hex, hex_to_bytes};
+    use crate::xor::*;
+
+    #[test]
+    fn test_rolling_xor() {
+        let b: Vec<u8> = vec![1, 1, 0, 0];
+        let k: Vec<u8> = vec![1, 0];
+
+        assert_eq!(rolling_xor(&b, &k), Ok(vec![0, 1, 1, 0]));
+        assert_eq!(rolling_xor(&b, &[]), Err(XorError::EmptyKey));
+    }
+
+    #[test]
+    fn test_c2() {
+        let b1 = &hex_to_bytes("1c0111001f010100061a024b53535009181c").unwrap();
+        let b2 = &hex_to_bytes("686974207468652062756c6c277320657965").unwrap();
+
+        let res = "746865206b696420646f6e277420706c6179";
+
+        assert_eq!(bytes_to_hex(&fixed_xor(b1, b2).unwrap()), res);
+    }
+
+    #[test]
+    fn test_c5() {
+        let s1 = "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+
+        let xrs1 = rolling_xor(s1.as_bytes(), b"ICE").unwrap();
+
+        let res = bytes_to_hex(&xrs1);
+        let expected = "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f";
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_xor_into() {
+        let src: Vec<u8> = (0..37).collect();
+        let mut dst: Vec<u8> = (0..37).map(|i| i * 7).collect();
+        let expected: Vec<u8> = dst.iter().zip(&src).map(|(a, b)| a ^ b).collect();
+
+        xor_into(&mut dst, &src).unwrap();
+        assert_eq!(dst, expected);
+
+        assert_eq!(
+            xor_into(&mut dst, &src[1..]),
+            Err(XorError::LengthMismatch {
+                left: 37,
+                right: 36
+            })
+        );
+        assert_eq!(
+            fixed_xor(b"ab", b"abc"),
+            Err(XorError::LengthMismatch { left: 2, right: 3 })
+        );
+    }
+
+    #[test]
+    fn test_xor_assign_matches_bytewise() {
+        let data: Vec<u8> = (0..500).map(|i| (i * 13) as u8).collect();
+        for key_len in [1, 3, 7, 16, 63, 64, 65, 200] {
+            let key: Vec<u8> = (0..key_len).map(|i| (i * 29 + 5) as u8).collect();
+            let expected: Vec<u8> = data
+                .iter()
+                .enumerate()
+                .map(|(i, &b)| b ^ key[i % key.len()])
+                .collect();
+
+            let mut buf = data.clone();
+            xor_assign(&mut buf, &key).unwrap();
+            assert_eq!(buf, expected, "key_len {key_len}");
+        }
+    }
+
+    #[test]
+    fn test_keystreams_agree() {
+        let data: Vec<u8> = (0..300).map(|i| (i * 11 + 3) as u8).collect();
+        for key in [&b"k"[..], b"ICE", b"YELLOW SUBMARINE", &[0x5a; 70]] {
+            let expected: Vec<u8> = data
+                .iter()
+                .zip(key.iter().cycle())
+                .map(|(d, k)| d ^ k)
+                .collect();
+
+            let mut whole = data.clone();
+            apply_keystream(&mut whole, RepeatingKey::new(key).unwrap());
+            assert_eq!(whole, expected);
+
+            let mut from_iter = data.clone();
+            apply_keystream(&mut from_iter, key.iter().copied().cycle());
+            assert_eq!(from_iter, expected);
+
+            // Uneven pieces continue the same keystream.
+            for step in [1, 5, 17, 64] {
+                let mut repeating = RepeatingKey::new(key).unwrap();
+                let mut iter = key.iter().copied().cycle();
+                let mut by_key = data.clone();
+                let mut by_iter = data.clone();
+                for (a, b) in by_key.chunks_mut(step).zip(by_iter.chunks_mut(step)) {
+                    repeating.apply(a);
+                    apply_keystream(b, iter.by_ref());
+                }
+                assert_eq!(by_key, expected, "step {step}");
+                assert_eq!(by_iter, expected, "step {step}");
+            }
+
+            if let [k] = key {
+                let mut single = data.clone();
+                apply_keystream(&mut single, SingleByte(*k));
+                assert_eq!(single, expected);
+            }
+        }
+
+        assert_eq!(RepeatingKey::new(b""), Err(XorError::EmptyKey));
+
+        let mut short = *b"abcd";
+        apply_keystream(&mut short, [1u8, 1].into_iter());
+        assert_eq!(&short, b"`ccd");
+    }
+}
@@ -0,0 +1,170 @@
+use std::io::Read;
+
+use aws_lc_rs::cipher::{
+    AES_128, DecryptionContext, PaddedBlockDecryptingKey, PaddedBlockEncryptingKey,
+    UnboundCipherKey,
+};
+use crypto_pals::{
+    aes::Aes128,
+    analysis::{
+        BreakOptions, Scorer, break_repeating_key_xor, crack_columns, crack_single_byte_xor,
+        detect_single_byte_xor, rank_ecb,
+        scoring::{BigramLogLikelihood, ChiSquared, Heuristic, PrintableRatio},
+    },
+    block::transpose_blocks,
+    encoding::{Encoding, hex_to_bytes, load_ciphertext, open_b64},
+    modes::Ecb,
+    padding::pkcs7_unpad,
+};
+
+#[test]
+fn test_aes_128_ebc_round_trip() {
+    let key_text = "YELLOW SUBMARINE";
+    let key_bytes = key_text.as_bytes();
+
+    let original_message = b"This is a secret message!";
+    let mut buffer = Vec::from(&original_message[..]);
+
+    let unbound_key = UnboundCipherKey::new(&AES_128, key_bytes).unwrap();
+    let encrypting_key = PaddedBlockEncryptingKey::ecb_pkcs7(unbound_key).unwrap();
+
+    encrypting_key.encrypt(&mut buffer).unwrap();
+    let unbound_key = UnboundCipherKey::new(&AES_128, key_bytes).unwrap();
+    let decrypting_key = PaddedBlockDecryptingKey::ecb_pkcs7(unbound_key).unwrap();
+    let mut in_out_buffer = buffer;
+    let plaintext = decrypting_key
+        .decrypt(&mut in_out_buffer, DecryptionContext::None)
+        .unwrap();
+
+    assert_eq!(
+        "This is a secret message!",
+        String::from_utf8_lossy(plaintext)
+    );
+}
+
+#[test]
+fn test_c3() {
+    let hex_str = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
+    let bytes = hex_to_bytes(hex_str).unwrap();
+
+    let best = &crack_single_byte_xor(&bytes, &ChiSquared, 1)[0];
+
+    let decrypted_message = String::from_utf8_lossy(&best.plaintext);
+    println!("Best key: {}", best.key as char);
+    println!("Decrypted message: {}", decrypted_message);
+    assert_eq!(decrypted_message, "Cooking MC's like a pound of bacon");
+}
+
+#[test]
+fn test_c4() {
+    let ciphertext = load_ciphertext("./static/4.txt").expect("decode 4.txt");
+    assert_eq!(ciphertext.encoding, Encoding::HexLines);
+
+    let hits = detect_single_byte_xor(&ciphertext.messages, &ChiSquared, 5);
+    let best = &hits[0].candidate;
+    let decrypted_message = String::from_utf8_lossy(&best.plaintext);
+    println!("Best key: {} (line {})", best.key as char, hits[0].line);
+    println!("Decrypted message: {}", decrypted_message);
+    assert_eq!(decrypted_message, "Now that the party is jumping\n");
+}
+
+#[test]
+fn test_c6() {
+    let ciphertext = load_ciphertext("./static/6.txt").expect("decode 6.txt");
+    assert_eq!(ciphertext.encoding, Encoding::Base64);
+    let raw = ciphertext.messages.concat();
+
+    let recovered = break_repeating_key_xor(&raw, BreakOptions::DEFAULT).expect("break 6.txt");
+
+    println!(
+        "Discovered key: {}",
+        String::from_utf8_lossy(&recovered.key)
+    );
+    println!(
+        "Decrypted message:\n{}",
+        String::from_utf8_lossy(&recovered.plaintext)
+    );
+    assert_eq!(recovered.key, b"Terminator X: Bring the noise");
+    assert!(
+        recovered
+            .plaintext
+            .starts_with(b"I'm back and I'm ringin' the bell")
+    );
+    assert_eq!(recovered.confidence, 1.0);
+}
+
+#[test]
+fn test_c7() {
+    let key_text = "YELLOW SUBMARINE";
+    let key_bytes = key_text.as_bytes();
+
+    let mut in_out_buffer = Vec::new();
+    open_b64("./static/7.txt")
+        .and_then(|mut reader| reader.read_to_end(&mut in_out_buffer))
+        .expect("decode 7.txt");
+
+    let mut ours = in_out_buffer.clone();
+    Ecb::new(Aes128::new(key_bytes.try_into().unwrap()))
+        .decrypt(&mut ours)
+        .unwrap();
+
+    let unbound_key = UnboundCipherKey::new(&AES_128, key_bytes).unwrap();
+    let decrypting_key = PaddedBlockDecryptingKey::ecb_pkcs7(unbound_key).unwrap();
+    let plaintext = decrypting_key
+        .decrypt(&mut in_out_buffer, DecryptionContext::None)
+        .unwrap();
+
+    println!("Decrypted: {}", String::from_utf8_lossy(plaintext));
+    assert!(plaintext.starts_with(b"I'm back and I'm ringin' the bell"));
+    assert_eq!(pkcs7_unpad(&ours), Ok(&plaintext[..]));
+}
+
+#[test]
+fn test_c8() {
+    let ciphertext = load_ciphertext("./static/8.txt").expect("decode 8.txt");
+    assert_eq!(ciphertext.encoding, Encoding::HexLines);
+
+    let hits = rank_ecb(&ciphertext.messages, 16);
+    let best = hits[0];
+    println!(
+        "Detected AES in ECB mode! Line: {} Repeated blocks: {}",
+        best.line, best.report.repeated_blocks
+    );
+    assert!(best.report.repeated_blocks > 0);
+    assert_eq!(hits[1].report.repeated_blocks, 0);
+    assert_eq!(best.line, 132);
+}
+
+/// Runs challenges 3, 4 and 6 under every scorer and reports which recover
+/// the known plaintexts.
+#[test]
+fn test_scorer_accuracy() {
+    let c3 = hex_to_bytes("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736")
+        .unwrap();
+    let c4 = load_ciphertext("./static/4.txt").unwrap().messages;
+    let c6 = load_ciphertext("./static/6.txt").unwrap().messages.concat();
+
+    // Printable ratio cannot tell a key from the same key with 0x20 flipped
+    // (letter case swaps), so it is only expected to work as a filter.
+    let scorers: [(&str, &dyn Scorer, bool); 4] = [
+        ("heuristic", &Heuristic, true),
+        ("printable", &PrintableRatio, false),
+        ("chi-squared", &ChiSquared, true),
+        ("bigram", &BigramLogLikelihood::new(), true),
+    ];
+
+    for (name, scorer, expect_solved) in scorers {
+        let c3_ok = crack_single_byte_xor(&c3, scorer, 1)[0].key == b'X';
+        let c4_ok = detect_single_byte_xor(&c4, scorer, 1)[0]
+            .candidate
+            .plaintext
+            == b"Now that the party is jumping\n";
+        let (key, _) = crack_columns(transpose_blocks(&c6, 29), scorer);
+        let c6_ok = key.iter().collect::<String>() == "Terminator X: Bring the noise";
+
+        println!("{name:>12}: c3 {c3_ok} c4 {c4_ok} c6 {c6_ok}");
+        if expect_solved {
+            assert!(c3_ok && c4_ok && c6_ok, "{name}");
+        }
+    }
+}